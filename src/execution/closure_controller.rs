@@ -0,0 +1,84 @@
+use super::{polled_controller::PolledController, Robot};
+
+/// Controller made from a closure, for quick experiments and tests without
+/// writing a controller type. The closure is called every tick and anything
+/// it captures is kept between runs. Give it to `with_controller` on either
+/// controller wrapper.
+/// ```
+/// # use maze::execution::{closure_controller::ClosureController, polled_controller::PolledControllerWrapper, robot::DefaultRobot, Facing, Robot};
+/// let mut turns = 0;
+/// let controller = ClosureController::new(move |robot: &mut DefaultRobot| {
+///     turns += 1;
+///     robot.face(Facing::Right);
+/// });
+/// let wrapper = PolledControllerWrapper::<DefaultRobot, _>::with_controller(controller);
+/// ```
+pub struct ClosureController<F, G = fn()> {
+    control: F,
+    reset: G,
+}
+
+impl<F> ClosureController<F> {
+    pub fn new(control: F) -> Self {
+        ClosureController { control, reset: || {} }
+    }
+}
+
+impl<F, G> ClosureController<F, G> {
+    /// Also call `reset` each time the robot is put back at the start for another run
+    pub fn with_reset<H: FnMut()>(self, reset: H) -> ClosureController<F, H> {
+        ClosureController { control: self.control, reset }
+    }
+}
+
+impl<R: Robot, F: FnMut(&mut R), G: FnMut()> PolledController<R> for ClosureController<F, G> {
+    fn control_robot(&mut self, robot: &mut R) {
+        (self.control)(robot)
+    }
+
+    fn reset(&mut self) {
+        (self.reset)()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, sync::{Arc, Mutex}};
+
+    use super::*;
+    use crate::{
+        execution::{
+            polled_controller::PolledControllerWrapper, robot::DefaultRobot, threaded_controller::ThreadedControllerWrapper,
+            threaded_robot::ThreadedRobot, Controller, Heading, RunOutcome, Tile,
+        },
+        generation::Maze,
+        Point,
+    };
+
+    #[test]
+    fn closures_drive_both_wrappers() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+        let resets = Rc::new(Cell::new(0));
+
+        let counter = resets.clone();
+        let controller = ClosureController::new(|robot: &mut DefaultRobot| robot.set_heading(Heading::East).unwrap())
+            .with_reset(move || counter.set(counter.get() + 1));
+        let mut polled = PolledControllerWrapper::with_controller(controller);
+        polled.set_runs(3);
+        polled.set_maze(maze.clone());
+        assert_eq!(polled.start(), RunOutcome::ReachedGoal);
+        assert_eq!(resets.get(), 2);
+
+        let mut ticks = 0;
+        let controller = ClosureController::new(|robot: &mut ThreadedRobot| {
+            ticks += 1;
+            robot.set_heading(Heading::East).unwrap();
+        });
+        let mut threaded = ThreadedControllerWrapper::with_controller(controller, Arc::new(Mutex::new(true)), Arc::new(Mutex::new(0)), Default::default());
+        threaded.set_maze(maze);
+        assert_eq!(threaded.start(), RunOutcome::ReachedGoal);
+        drop(threaded);
+        assert_eq!(ticks, 3);
+    }
+}
@@ -0,0 +1,111 @@
+//! Runs the same controllers against every robot and wrapper, checking they
+//! all behave the same way.
+
+use crate::{
+    generation::{blank_generator::BlankGenerator, Generator, Maze},
+    solving::{path_solver::ShortestPathSolver, Solver},
+    Point,
+};
+
+use super::{
+    polled_controller::{PolledController, PolledControllerWrapper},
+    private,
+    robot::DefaultRobot,
+    threaded_controller::ThreadedControllerWrapper,
+    threaded_robot::ThreadedRobot,
+    trace::RunTrace,
+    wall_follower_controller::WallFollowerController,
+    Controller, Heading, Robot, RobotStatistics, RunOutcome, Tile,
+};
+
+/// Follows the shortest path, worked out from the full map each tick
+#[derive(Default)]
+struct MapFollower;
+
+impl<R: Robot<Tiles = Tile>> PolledController<R> for MapFollower {
+    fn control_robot(&mut self, robot: &mut R) {
+        let location = robot.get_location().unwrap();
+        let next = {
+            let maze = robot.get_maze().unwrap();
+            let solution = ShortestPathSolver::new().solve(&maze).unwrap();
+            let path = solution.get_path();
+            path.iter().position(|point| *point == location).and_then(|index| path.get(index + 1).copied())
+        };
+
+        if let Some(heading) = next.and_then(|next| Heading::ALL.into_iter().find(|heading| heading.next_point(location) == Some(next))) {
+            robot.set_heading(heading).unwrap();
+        }
+    }
+}
+
+/// The parts of a run which should match whichever robot did it
+fn summary(runs: &[RobotStatistics]) -> Vec<(Option<RunOutcome>, i64, i64, i64, usize)> {
+    runs.iter()
+        .map(|run| (run.get_outcome(), run.get_steps(), run.get_collisions(), run.get_turns(), run.get_unique_cells()))
+        .collect()
+}
+
+fn maze() -> Maze<Tile> {
+    let mut maze = BlankGenerator::new().generate_maze();
+    for y in 1..15 {
+        maze.set_cell(Point(6, y), Tile::Wall);
+    }
+    for x in 10..19 {
+        maze.set_cell(Point(x, 10), Tile::Wall);
+    }
+    maze
+}
+
+fn run_polled<R, C>() -> (Vec<RobotStatistics>, Vec<RunTrace>)
+where
+    R: Robot<Tiles = Tile> + private::Robot,
+    C: PolledController<R> + Default,
+{
+    let mut wrapper = PolledControllerWrapper::<R, C>::new();
+    wrapper.set_runs(2);
+    wrapper.set_maze(maze());
+    assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+    (wrapper.get_history().to_vec(), wrapper.get_traces().to_vec())
+}
+
+fn run_threaded<C: PolledController<ThreadedRobot> + Default>() -> (Vec<RobotStatistics>, Vec<RunTrace>) {
+    let mut wrapper = ThreadedControllerWrapper::<C>::default();
+    wrapper.set_runs(2);
+    wrapper.set_maze(maze());
+    assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+    (wrapper.get_history().to_vec(), wrapper.get_traces().to_vec())
+}
+
+fn check_conformance<C>()
+where
+    C: PolledController<DefaultRobot> + PolledController<ThreadedRobot> + Default,
+{
+    let (expected, expected_traces) = run_polled::<DefaultRobot, C>();
+    assert_eq!(expected.len(), 2);
+
+    for (runs, traces) in [run_polled::<ThreadedRobot, C>(), run_threaded::<C>()] {
+        assert_eq!(summary(&runs), summary(&expected));
+        assert_eq!(traces.len(), expected_traces.len());
+        for (trace, expected) in traces.iter().zip(&expected_traces) {
+            assert_eq!(expected.verify(trace), Ok(()));
+        }
+    }
+}
+
+#[test]
+fn map_follower_conforms() {
+    check_conformance::<MapFollower>();
+}
+
+#[test]
+fn wall_follower_conforms() {
+    check_conformance::<WallFollowerController>();
+}
+
+#[test]
+fn threaded_robot_shares_its_maze() {
+    let mut robot = ThreadedRobot::default();
+    private::Robot::set_maze(&mut robot, Box::new(maze()));
+    assert_eq!(*robot.get_maze().unwrap(), maze());
+    assert_eq!(*robot.get_maze_ref().read().unwrap(), maze());
+}
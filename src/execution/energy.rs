@@ -0,0 +1,134 @@
+use std::cell::Cell;
+
+use super::{Facing, Heading};
+
+/// Opt-in model of a robot running off a battery. Each action uses up
+/// some of the battery and the run ends once it is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnergyModel {
+    /// Energy the battery holds at the start of each run
+    pub capacity: u64,
+    /// Energy used for each tick spent moving, so slow tiles use more
+    pub movement: u64,
+    /// Energy used for each quarter turn
+    pub turn: u64,
+    /// Energy used for each `look` or `range`
+    pub sense: u64,
+    /// Energy used when bumping into a wall
+    pub collision: u64,
+}
+
+impl Default for EnergyModel {
+    fn default() -> Self {
+        EnergyModel {
+            capacity: 1000,
+            movement: 1,
+            turn: 1,
+            sense: 1,
+            collision: 5,
+        }
+    }
+}
+
+/// An energy model along with how much has been used this run.
+/// Without a model nothing is counted and the battery never runs out.
+#[derive(Debug, Default)]
+pub(crate) struct Battery {
+    model: Option<EnergyModel>,
+    used: Cell<u64>,
+}
+
+impl Battery {
+    pub(crate) fn new(model: Option<EnergyModel>) -> Self {
+        Battery {
+            model,
+            used: Cell::new(0),
+        }
+    }
+
+    fn drain(&self, cost: impl FnOnce(&EnergyModel) -> u64) {
+        if let Some(model) = &self.model {
+            self.used.set(self.used.get().saturating_add(cost(model)));
+        }
+    }
+
+    pub(crate) fn sense(&self) {
+        self.drain(|model| model.sense)
+    }
+
+    /// Turn to face a relative direction
+    pub(crate) fn face(&self, face: Facing) {
+        let quarters = match face {
+            Facing::Ahead => 0,
+            Facing::Left | Facing::Right => 1,
+            Facing::Behind => 2,
+        };
+        self.drain(|model| model.turn.saturating_mul(quarters))
+    }
+
+    /// Turn from one absolute heading to another, whichever way round is shorter
+    pub(crate) fn turn(&self, from: Heading, to: Heading) {
+        let quarters = (to as u64 + 4 - from as u64) % 4;
+        self.drain(|model| model.turn.saturating_mul(quarters.min(4 - quarters)))
+    }
+
+    /// Spend `ticks` moving, or spinning the wheels when stalled
+    pub(crate) fn movement(&self, ticks: u64) {
+        self.drain(|model| model.movement.saturating_mul(ticks))
+    }
+
+    pub(crate) fn collision(&self) {
+        self.drain(|model| model.collision)
+    }
+
+    pub(crate) fn used(&self) -> u64 {
+        self.used.get()
+    }
+
+    /// Energy left in the battery, `None` if there is no battery
+    pub(crate) fn remaining(&self) -> Option<u64> {
+        self.model.map(|model| model.capacity.saturating_sub(self.used()))
+    }
+
+    /// Fully charge the battery for a new run
+    pub(crate) fn recharge(&self) {
+        self.used.set(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_drain_the_battery() {
+        let battery = Battery::new(Some(EnergyModel { capacity: 20, ..Default::default() }));
+        battery.face(Facing::Behind);
+        battery.turn(Heading::North, Heading::West);
+        battery.sense();
+        battery.movement(5);
+        battery.collision();
+        assert_eq!(battery.used(), 14);
+        assert_eq!(battery.remaining(), Some(6));
+
+        battery.movement(10);
+        assert_eq!(battery.remaining(), Some(0));
+        battery.recharge();
+        assert_eq!(battery.remaining(), Some(20));
+
+        let unlimited = Battery::new(None);
+        unlimited.movement(100);
+        assert_eq!(unlimited.used(), 0);
+        assert_eq!(unlimited.remaining(), None);
+    }
+
+    #[test]
+    fn huge_costs_saturate() {
+        let battery = Battery::new(Some(EnergyModel { capacity: u64::MAX, movement: u64::MAX, turn: u64::MAX, ..Default::default() }));
+        battery.face(Facing::Behind);
+        battery.turn(Heading::North, Heading::South);
+        battery.movement(5);
+        assert_eq!(battery.used(), u64::MAX);
+        assert_eq!(battery.remaining(), Some(0));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+use crate::Point;
+
+use super::{
+    map::{MapCell, RobotMap},
+    polled_controller::PolledController,
+    Facing, Heading, Robot,
+};
+
+/// Steps from each cell to the goal, `None` where the goal can't be reached
+type Distances = Vec<Vec<Option<usize>>>;
+
+/// Micromouse style flood fill. Looks around every tick and works out the
+/// distance from every cell to the goal over the robot's map of what it has
+/// sensed, counting cells it hasn't seen yet as open, then moves to
+/// whichever neighbour is nearest.
+///
+/// The robot keeps its map between runs on the same maze and clears it when
+/// the maze changes, so each run follows the shortest route given
+/// everything learned on the runs before and the runs get faster. Once the
+/// route only goes through cells known to be open it is the shortest there is.
+///
+/// Needs the position, compass and discovered map sensors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FloodFillController;
+
+impl FloodFillController {
+    /// Flood out from the goal across the map, going through anything not
+    /// known to be a wall
+    fn flood(map: &RobotMap, goal: Point) -> Distances {
+        let (width, height) = (map.get_width(), map.get_height());
+        let passable = |point: Point| point.get_x() < width && point.get_y() < height && map.get(point) != MapCell::Wall;
+
+        let mut distances = vec![vec![None; width]; height];
+        if !passable(goal) {
+            return distances;
+        }
+        distances[goal.get_y()][goal.get_x()] = Some(0);
+        let mut queue = VecDeque::from([(goal, 0)]);
+        while let Some((point, distance)) = queue.pop_front() {
+            for next in Heading::ALL.iter().filter_map(|heading| heading.next_point(point)) {
+                if passable(next) && distances[next.get_y()][next.get_x()].is_none() {
+                    distances[next.get_y()][next.get_x()] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+
+    fn distance(distances: &Distances, point: Point) -> Option<usize> {
+        distances.get(point.get_y()).and_then(|row| row.get(point.get_x())).copied().flatten()
+    }
+}
+
+impl<R: Robot> PolledController<R> for FloodFillController {
+    fn control_robot(&mut self, robot: &mut R) {
+        let (Ok(location), Ok(goal), Ok(heading)) = (robot.get_location(), robot.get_goal_location(), robot.get_heading()) else {
+            return;
+        };
+
+        // Looking records what is around on the robot's map
+        for face in [Facing::Ahead, Facing::Right, Facing::Behind, Facing::Left] {
+            robot.look(face);
+        }
+        let Ok(map) = robot.get_map() else {
+            return;
+        };
+        let distances = Self::flood(&map, goal);
+
+        // Ties go to whichever needs the least turning
+        let nearest = [Facing::Ahead, Facing::Right, Facing::Left, Facing::Behind]
+            .into_iter()
+            .filter_map(|face| {
+                let next = heading.augment_heading(face).next_point(location)?;
+                Some((Self::distance(&distances, next)?, face))
+            })
+            .min_by_key(|(distance, _)| *distance);
+
+        if let Some((_, face)) = nearest {
+            robot.face(face);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution::{limits::RunLimits, polled_controller::PolledControllerWrapper, robot::DefaultRobot, Controller, RunOutcome, Tile},
+        generation::{blank_generator::BlankGenerator, Generator},
+    };
+
+    #[test]
+    fn second_run_is_faster() {
+        // Open room with the goal walled off from below, so it can
+        // only be got to along the top
+        let mut maze = BlankGenerator::new().generate_maze();
+        for y in 5..19 {
+            maze.set_cell(Point(15, y), Tile::Wall);
+        }
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, FloodFillController>::new();
+        wrapper.set_limits(RunLimits { max_steps: Some(1_000), ..Default::default() });
+        wrapper.set_runs(2);
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+
+        let history = wrapper.get_history();
+        assert!(history.iter().all(|run| run.goal_reached() && run.get_collisions() == 0));
+        assert!(history[1].get_steps() < history[0].get_steps());
+        assert_eq!(history[1].get_efficiency(), Some(1.0));
+
+        // The map it follows is the robot's, which starts again on a new maze
+        assert!(wrapper.get_robot().get_map().unwrap().known_cells() > 100);
+        let mut open = BlankGenerator::new().generate_maze();
+        open.set_start(Point(18, 18));
+        open.set_finish(Point(12, 18));
+        wrapper.set_runs(1);
+        wrapper.set_maze(open);
+        assert_eq!(wrapper.get_robot().get_map().unwrap().known_cells(), 1);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+        assert_eq!(wrapper.get_statistics().get_efficiency(), Some(1.0));
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::{Duration, Instant};
+
+use super::{Robot, RunOutcome};
+
+/// Limits which stop a run early, so a controller which never reaches
+/// the goal doesn't run forever. Every limit is off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunLimits {
+    /// Most moves the robot can make
+    pub max_steps: Option<i64>,
+    /// Most times the robot can bump into a wall
+    pub max_collisions: Option<i64>,
+    /// Longest the run can take in real time, including any delay between moves
+    pub timeout: Option<Duration>,
+}
+
+impl RunLimits {
+    /// Which limit the run has hit, if any. `started` is when the run began.
+    pub fn check<R: Robot>(&self, robot: &R, started: Instant) -> Option<RunOutcome> {
+        if self.max_steps.is_some_and(|max| robot.get_steps() >= max) {
+            Some(RunOutcome::OutOfSteps)
+        } else if self.max_collisions.is_some_and(|max| robot.get_collisions() >= max) {
+            Some(RunOutcome::TooManyCollisions)
+        } else if self.timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            Some(RunOutcome::TimedOut)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::Point;
+
+use super::{sensors::{self, LocalView}, Facing, Heading, TileType};
+
+/// What the robot knows about a single cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MapCell {
+    /// Not sensed yet
+    #[default]
+    Unknown,
+    /// Last seen as something the robot can move onto
+    Open,
+    /// Last seen as a wall, or bumped into
+    Wall,
+}
+
+/// The robot's own picture of the maze, built up from what it has sensed.
+/// Uses maze coordinates, so reading it needs the position sensor as well as
+/// the discovered map sensor.
+/// The map is kept when the robot is reset and cleared when the maze changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotMap {
+    width: usize,
+    height: usize,
+    cells: Vec<Vec<MapCell>>,
+}
+
+impl RobotMap {
+    /// Create a map of the given size with every cell unknown
+    pub fn new(width: usize, height: usize) -> Self {
+        RobotMap {
+            width,
+            height,
+            cells: vec![vec![MapCell::Unknown; width]; height],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// What is known about the cell, `Unknown` for anything off the map
+    pub fn get(&self, point: Point) -> MapCell {
+        self.cells
+            .get(point.get_y())
+            .and_then(|row| row.get(point.get_x()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn is_known(&self, point: Point) -> bool {
+        self.get(point) != MapCell::Unknown
+    }
+
+    /// Number of cells which have been sensed
+    pub fn known_cells(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| **cell != MapCell::Unknown).count()
+    }
+
+    /// Open cells next to at least one unknown cell, the edge of what has been explored
+    pub fn frontier(&self) -> Vec<Point> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point(x, y)))
+            .filter(|point| self.get(*point) == MapCell::Open)
+            .filter(|point| {
+                Heading::ALL
+                    .iter()
+                    .filter_map(|heading| heading.next_point(*point))
+                    .any(|next| next.get_x() < self.width && next.get_y() < self.height && !self.is_known(next))
+            })
+            .collect()
+    }
+
+    /// Record what is in a cell, ignoring anything off the map
+    pub(crate) fn mark(&mut self, point: Point, cell: MapCell) {
+        if let Some(current) = self.cells.get_mut(point.get_y()).and_then(|row| row.get_mut(point.get_x())) {
+            *current = cell;
+        }
+    }
+
+    /// Record a tile seen at this point
+    pub(crate) fn mark_tile<T: TileType>(&mut self, point: Point, tile: &T) {
+        self.mark(point, if tile.can_walk() { MapCell::Open } else { MapCell::Wall });
+    }
+
+    /// Record a `look` from `from` towards `heading`
+    pub(crate) fn record_look<T: TileType>(&mut self, from: Point, heading: Heading, tile: &T) {
+        if let Some(point) = heading.next_point(from) {
+            self.mark_tile(point, tile);
+        }
+    }
+
+    /// Record a `range` reading. The cell after the open ones is only known
+    /// to be a wall if the reading stopped short of the limit.
+    pub(crate) fn record_range(&mut self, from: Point, heading: Heading, range: usize, limit: Option<usize>) {
+        let mut point = Some(from);
+        for _ in 0..range {
+            point = point.and_then(|point| heading.next_point(point));
+            if let Some(point) = point {
+                self.mark(point, MapCell::Open);
+            }
+        }
+
+        if limit.is_none_or(|limit| range < limit) {
+            if let Some(point) = point.and_then(|point| heading.next_point(point)) {
+                self.mark(point, MapCell::Wall);
+            }
+        }
+    }
+
+    /// Record everything in a local view of the robot at `location` facing `heading`
+    pub(crate) fn record_view<T: TileType>(&mut self, view: &LocalView<T>, location: Point, heading: Heading) {
+        let r = view.get_radius() as isize;
+        for ahead in -r..=r {
+            for right in -r..=r {
+                let point = sensors::offset(location, heading, ahead)
+                    .and_then(|point| sensors::offset(point, heading.augment_heading(Facing::Right), right));
+                if let (Some(point), Some(tile)) = (point, view.get(ahead, right)) {
+                    self.mark_tile(point, tile);
+                }
+            }
+        }
+    }
+
+    /// Clear and resize the map for a new maze
+    pub(crate) fn resize(&mut self, width: usize, height: usize) {
+        *self = RobotMap::new(width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::Tile;
+
+    #[test]
+    fn records_readings() {
+        let mut map = RobotMap::new(5, 3);
+        map.mark(Point(0, 1), MapCell::Open);
+        map.record_range(Point(0, 1), Heading::East, 2, None);
+        map.record_look(Point(0, 1), Heading::North, &Tile::Wall);
+
+        assert_eq!(map.get(Point(2, 1)), MapCell::Open);
+        assert_eq!(map.get(Point(3, 1)), MapCell::Wall);
+        assert_eq!(map.get(Point(0, 0)), MapCell::Wall);
+        assert_eq!(map.get(Point(9, 9)), MapCell::Unknown);
+        assert_eq!(map.known_cells(), 5);
+        assert_eq!(map.frontier(), vec![Point(0, 1), Point(1, 1), Point(2, 1)]);
+
+        // Stopping at the limit says nothing about the next cell
+        map.record_range(Point(0, 1), Heading::South, 1, Some(1));
+        assert_eq!(map.get(Point(0, 2)), MapCell::Open);
+        assert!(!map.is_known(Point(0, 3)));
+    }
+}
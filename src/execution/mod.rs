@@ -1,10 +1,36 @@
-use crate::{Point, generation::Maze};
+use std::time::Duration;
 
+use crate::{Point, generation::Maze};
+use map::RobotMap;
+use rules::Inventory;
+use sensors::{LocalView, MazeRef, SensorConfig, SensorError};
+use trail::VisitTrail;
+
+#[cfg(test)]
+mod conformance;
+pub mod closure_controller;
+pub mod energy;
+pub mod flood_fill_controller;
+pub mod limits;
+pub mod map;
+pub mod noise;
+pub mod observer;
+pub mod pledge_controller;
 pub mod polled_controller;
+pub mod q_learning_controller;
 pub mod threaded_controller;
 pub mod random_controller;
 pub mod robot;
+pub mod rules;
+pub mod statistics;
+pub mod sensors;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod threaded_robot;
+pub mod trace;
+pub mod trail;
+pub mod tremaux_controller;
+pub mod wall_follower_controller;
 
 /// This trait is what the student implements -- 
 /// the brains of the robot is a type which can run certain
@@ -19,8 +45,10 @@ pub trait Controller<T: Robot<Tiles=K>, K: TileType + Default> {
     /// Set the Maze the controller/robot operates on
     fn set_maze(&mut self, maze: Maze<K>);
 
-    /// Called when the controller is started
-    fn start(&mut self);
+    /// Called when the controller is started. Each run goes until the robot
+    /// reaches the goal or the run otherwise ends, then the robot is put back
+    /// at the start for the next. Says how the last run ended.
+    fn start(&mut self) -> RunOutcome;
 
     /// Called when the controller is reset
     fn reset(&mut self);
@@ -40,7 +68,7 @@ pub trait Controller<T: Robot<Tiles=K>, K: TileType + Default> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Heading {
     North = 1000,
     East,
@@ -60,9 +88,10 @@ impl From<u32> for Heading {
 }
 
 impl Heading {
-    pub fn augment_heading(&self, face: Facing) -> Self {
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
 
-        let h = match face {
+    pub fn augment_heading(&self, face: Facing) -> Self {
+        match face {
             Facing::Ahead => *self,
             Facing::Behind => match self {
                 Heading::North => Heading::South,
@@ -82,12 +111,32 @@ impl Heading {
                 Heading::South => Heading::West,
                 Heading::West => Heading::North
             }
-        };
-        h
+        }
+    }
+
+    /// Which way to face to turn from this heading to `to`
+    pub fn facing_towards(&self, to: Heading) -> Facing {
+        match (to as u32 + 4 - *self as u32) % 4 {
+            0 => Facing::Ahead,
+            1 => Facing::Right,
+            2 => Facing::Behind,
+            _ => Facing::Left,
+        }
+    }
+
+    /// The neighbouring point in this direction, if it doesn't fall off the top or left of the grid
+    pub fn next_point(&self, point: Point) -> Option<Point> {
+        let Point(x, y) = point;
+        match self {
+            Heading::North => Some(Point(x, y.checked_sub(1)?)),
+            Heading::East => Some(Point(x + 1, y)),
+            Heading::South => Some(Point(x, y + 1)),
+            Heading::West => Some(Point(x.checked_sub(1)?, y)),
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Facing {
     Ahead = 2000,
     Right,
@@ -115,26 +164,250 @@ pub trait TileType {
     fn can_walk(&self) -> bool {
         !self.is_wall()
     }
+
+    /// Number of ticks it takes a robot to move onto this tile
+    fn cost(&self) -> u32 {
+        1
+    }
+
+    /// The tile with any marks a robot has left on it removed.
+    /// Used when comparing mazes by their structure only.
+    fn unmarked(&self) -> Self
+    where
+        Self: Sized + Clone,
+    {
+        self.clone()
+    }
+
+    /// Fixed bytes identifying the tile, what kind it is then anything it
+    /// carries. Used for maze fingerprints so they are the same on every
+    /// platform and build. By default only tells walls from everything else.
+    fn code(&self) -> [u8; 2] {
+        [self.is_wall() as u8, 0]
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum Tile {
+    #[default]
     Passage,
     BeenBefore,
     Wall,
+    /// Very slow going
+    Mud,
+    /// Slow going
+    Sand,
+    /// Quicker than a normal passage
+    Road,
+    /// Picked up when the robot moves onto it
+    Key(KeyColour),
+    /// Can only be passed while carrying the key of the same colour
+    Door(KeyColour),
+    /// Toggles every switch wall in the same group when the robot moves onto it
+    Switch(SwitchGroup),
+    /// Wall which opens and closes when a switch in its group is pressed.
+    /// `open` is how it starts in the maze.
+    SwitchWall { group: SwitchGroup, open: bool },
+    /// Moves the robot to the other teleporter on the same channel
+    Teleporter(u8),
+    /// Can only be moved onto while travelling in this heading
+    OneWay(Heading),
+    /// The robot keeps sliding in the same heading until it reaches
+    /// something it can't move onto, or a tile which isn't ice
+    Ice,
+    /// Destroys the robot when it moves onto it
+    Pit,
+    /// Takes this much health from the robot each time it moves onto it
+    Damage(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyColour {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl KeyColour {
+    pub const ALL: [KeyColour; 4] = [KeyColour::Red, KeyColour::Green, KeyColour::Blue, KeyColour::Yellow];
+}
+
+/// Group linking switches to the switch walls they open and close.
+/// Groups are numbered 0 to 63, anything higher can't be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct SwitchGroup(u8);
+
+impl SwitchGroup {
+    pub const MAX: u8 = 63;
+
+    /// The group with this number, `None` if it is above `SwitchGroup::MAX`
+    pub const fn new(group: u8) -> Option<Self> {
+        if group <= Self::MAX {
+            Some(SwitchGroup(group))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self) -> u8 {
+        self.0
+    }
 }
 
-impl Default for Tile {
-    fn default() -> Self {
-        Tile::Passage
+impl Tile {
+    /// Character used when drawing the tile to the console
+    pub fn symbol(&self) -> char {
+        match self {
+            Tile::Passage => ' ',
+            Tile::BeenBefore => '░',
+            Tile::Wall => '█',
+            Tile::Mud => '~',
+            Tile::Sand => '.',
+            Tile::Road => '=',
+            Tile::Key(colour) => match colour {
+                KeyColour::Red => 'r',
+                KeyColour::Green => 'g',
+                KeyColour::Blue => 'b',
+                KeyColour::Yellow => 'y',
+            },
+            Tile::Door(colour) => match colour {
+                KeyColour::Red => 'R',
+                KeyColour::Green => 'G',
+                KeyColour::Blue => 'B',
+                KeyColour::Yellow => 'Y',
+            },
+            Tile::Switch(_) => '%',
+            Tile::SwitchWall { open: false, .. } => '▓',
+            Tile::SwitchWall { open: true, .. } => ':',
+            Tile::Teleporter(channel) => char::from_digit(*channel as u32 % 10, 10).unwrap_or('@'),
+            Tile::OneWay(heading) => match heading {
+                Heading::North => '↑',
+                Heading::East => '→',
+                Heading::South => '↓',
+                Heading::West => '←',
+            },
+            Tile::Ice => '*',
+            Tile::Pit => 'O',
+            Tile::Damage(_) => '!',
+        }
     }
 }
 
 impl TileType for Tile {
     fn is_wall(&self) -> bool {
+        matches!(self, Tile::Wall | Tile::Door(_) | Tile::SwitchWall { open: false, .. })
+    }
+
+    fn cost(&self) -> u32 {
         match self {
-            Tile::Wall => true,
-            _ => false,
+            Tile::Road | Tile::Ice => 1,
+            Tile::Passage
+            | Tile::BeenBefore
+            | Tile::Wall
+            | Tile::Key(_)
+            | Tile::Door(_)
+            | Tile::Switch(_)
+            | Tile::SwitchWall { .. }
+            | Tile::Teleporter(_)
+            | Tile::OneWay(_)
+            | Tile::Pit
+            | Tile::Damage(_) => PASSAGE_COST,
+            Tile::Sand => 3,
+            Tile::Mud => 5,
+        }
+    }
+
+    fn unmarked(&self) -> Self {
+        match self {
+            Tile::BeenBefore => Tile::Passage,
+            tile => *tile,
+        }
+    }
+
+    fn code(&self) -> [u8; 2] {
+        match *self {
+            Tile::Passage => [0, 0],
+            Tile::BeenBefore => [1, 0],
+            Tile::Wall => [2, 0],
+            Tile::Mud => [3, 0],
+            Tile::Sand => [4, 0],
+            Tile::Road => [5, 0],
+            Tile::Key(colour) => [6, colour as u8],
+            Tile::Door(colour) => [7, colour as u8],
+            Tile::Switch(group) => [8, group.get()],
+            Tile::SwitchWall { group, open: false } => [9, group.get()],
+            Tile::SwitchWall { group, open: true } => [10, group.get()],
+            Tile::Teleporter(channel) => [11, channel],
+            Tile::OneWay(heading) => [12, heading as u8],
+            Tile::Ice => [13, 0],
+            Tile::Pit => [14, 0],
+            Tile::Damage(damage) => [15, damage],
+        }
+    }
+}
+
+/// Ticks it takes to move onto a plain `Tile::Passage`. Roads are quicker,
+/// so this is more than one tick.
+pub const PASSAGE_COST: u32 = 2;
+
+/// How long to sleep after a poll which took `ticks` ticks, so crossing a
+/// plain passage takes `delay` and other tiles take proportionally longer
+pub(crate) fn tick_delay(delay: i32, ticks: u64) -> i32 {
+    let ticks = i64::try_from(ticks.max(1)).unwrap_or(i64::MAX);
+    let delay = (delay as i64).saturating_mul(ticks) / PASSAGE_COST as i64;
+    i32::try_from(delay).unwrap_or(i32::MAX)
+}
+
+/// Health a robot starts each run with
+pub const ROBOT_HEALTH: u32 = 100;
+
+/// Heading a robot faces at the start of each run
+pub const START_HEADING: Heading = Heading::South;
+
+/// How a run came to an end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunOutcome {
+    /// The robot made it to the goal
+    ReachedGoal,
+    /// The robot fell into a pit or ran out of health
+    Destroyed,
+    /// The robot used up the steps it was allowed
+    OutOfSteps,
+    /// The robot bumped into walls more often than it was allowed
+    TooManyCollisions,
+    /// The run took longer than it was allowed
+    TimedOut,
+    /// The robot's battery ran flat
+    OutOfEnergy,
+    /// The run was stopped from outside before it finished
+    Aborted,
+}
+
+/// What happened when a controller wrapper was stepped once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    /// The robot moved, ending up here
+    Moved(Point),
+    /// The robot bumped into a wall
+    Collided,
+    /// The robot's wheels slipped and it didn't move
+    Stalled,
+    /// The run has ended, reaching the goal or otherwise
+    Finished(RunOutcome),
+}
+
+impl RunOutcome {
+    /// How the run has ended based on the robot alone, `None` if it can carry on
+    pub(crate) fn from_robot<R: Robot>(robot: &R) -> Option<Self> {
+        if robot.is_destroyed() {
+            Some(RunOutcome::Destroyed)
+        } else if robot.at_goal() {
+            Some(RunOutcome::ReachedGoal)
+        } else if robot.is_out_of_energy() {
+            Some(RunOutcome::OutOfEnergy)
+        } else {
+            None
         }
     }
 }
@@ -148,35 +421,88 @@ pub trait Robot: Default {
     /// Look at the relative facing direction and get the tile
     fn look(&self, face: Facing) -> Self::Tiles;
 
+    /// Number of open cells in the relative facing direction before the
+    /// next wall. May be capped by the range limit set on the controller.
+    fn range(&self, face: Facing) -> usize;
+
     /// Change the robot to face a certain way
     fn face(&mut self, face: Facing);
 
-    /// Set the robots absolute heading
-    fn set_heading(&mut self, heading: Heading);
+    /// Set the robots absolute heading. Needs the compass sensor.
+    fn set_heading(&mut self, heading: Heading) -> Result<(), SensorError>;
 
-    /// Get the robots current heading
-    fn get_heading(&self) -> Heading;
+    /// Get the robots current heading. Needs the compass sensor.
+    fn get_heading(&self) -> Result<Heading, SensorError>;
 
-    /// Get the location of the robot
-    fn get_goal_location(&self) -> Point;
+    /// Get the location of the goal. Needs the position sensor.
+    fn get_goal_location(&self) -> Result<Point, SensorError>;
     
-    /// Get the current location of the robot
-    fn get_location(&self) -> Point;
+    /// Get the current location of the robot. Needs the position sensor.
+    fn get_location(&self) -> Result<Point, SensorError>;
+
+    /// Get the current maze. Needs the full map sensor.
+    fn get_maze(&self) -> Result<MazeRef<'_, Self::Tiles>, SensorError>;
+
+    /// Get the tiles around the robot, relative to its heading. Needs the local view sensor.
+    fn get_local_view(&self) -> Result<LocalView<Self::Tiles>, SensorError>;
+
+    /// Get the angle in degrees to turn clockwise to face the goal,
+    /// between -180 and 180. Needs the goal bearing sensor.
+    fn get_goal_bearing(&self) -> Result<f64, SensorError>;
 
-    /// Get the current maze
-    fn get_maze(&self) -> &Maze<Self::Tiles>;
+    /// Is the robot currently on the goal. Always available.
+    fn at_goal(&self) -> bool;
 
     /// Sleep for a bit
     fn sleep(&self, time: i32);
 
     /// Stats methods TODO!
-    /// Get the number of times this robot has been run
+    /// Get the number of runs the robot has finished on the current maze
     fn get_runs(&self) -> i32 { 0 }
 
     fn get_steps(&self) -> i64 { 0 }
 
     fn get_collisions(&self) -> i64 { 0 }
 
+    /// Get the number of times the robot has changed heading this run
+    fn get_turns(&self) -> i64 { 0 }
+
+    /// Get the number of times the robot has used `look` or `range` this run
+    fn get_sensor_reads(&self) -> i64 { 0 }
+
+    /// Get the number of ticks spent moving this run. Moving onto a tile
+    /// costs that tile's `cost()`, bumping into a wall costs one tick.
+    fn get_time(&self) -> u64 { 0 }
+
+    /// Get the robot's remaining health
+    fn get_health(&self) -> u32 { ROBOT_HEALTH }
+
+    /// Has the robot been destroyed by a hazard this run
+    fn is_destroyed(&self) -> bool {
+        self.get_health() == 0
+    }
+
+    /// Get the energy left in the robot's battery, `None` if it doesn't have one
+    fn get_energy(&self) -> Option<u64> { None }
+
+    /// Get the energy the robot has used this run
+    fn get_energy_used(&self) -> u64 { 0 }
+
+    /// Has the robot's battery run flat this run
+    fn is_out_of_energy(&self) -> bool {
+        self.get_energy() == Some(0)
+    }
+
+    /// Get the keys the robot is carrying
+    fn get_inventory(&self) -> Inventory { Inventory::default() }
+
+    /// Get a copy of where the robot has been this run
+    fn get_trail(&self) -> VisitTrail { VisitTrail::default() }
+
+    /// Get a copy of the map the robot has built from what it has sensed,
+    /// in maze coordinates. Needs the discovered map and position sensors.
+    fn get_map(&self) -> Result<RobotMap, SensorError> { Err(SensorError::NoDiscoveredMap) }
+
     // Print Robot & Maze state out to console
     fn print(&self) { }
 
@@ -195,12 +521,36 @@ mod private {
         /// Set the current location of the robot
         fn set_location(&mut self, loc: Point);
 
-        /// Reset everything
+        /// Put the robot back at the start for another run on the same maze
         fn reset(&mut self);
 
         /// Set where the robots target is
         fn set_target_location(&mut self, loc: Point);
 
+        /// Set the furthest the range sensor can see, `None` for no limit
+        fn set_range_limit(&mut self, limit: Option<usize>);
+
+        /// Make the robot's sensors and movement unreliable, `None` for a perfect robot
+        fn set_noise(&mut self, noise: Option<super::noise::NoiseModel>);
+
+        /// Give the robot a battery which actions drain, `None` for unlimited energy
+        fn set_energy(&mut self, energy: Option<super::energy::EnergyModel>);
+
+        /// Choose which sensors the controller is allowed to use
+        fn set_sensors(&mut self, sensors: super::SensorConfig);
+
+        /// Only draw the parts of the maze the robot has discovered when printing
+        fn set_fog_of_war(&mut self, _fog: bool) {}
+
+        /// Fewest steps needed to get from the start to the goal, `None` if it can't be reached
+        fn shortest_path(&self) -> Option<usize> { None }
+
+        /// Where the robot really is, whatever its sensors allow
+        fn current_location(&self) -> Point;
+
+        /// Which way the robot is really facing, whatever its sensors allow
+        fn current_heading(&self) -> super::Heading;
+
         /// Advance the robot on. Define at crate level privacy 
         /// to stop an external robot controller calling this at the wrong time
         /// Should be implemented by the concrete Robot impl.
@@ -209,12 +559,22 @@ mod private {
 }
 
 // IRobotReport, RobotReport
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
 pub struct RobotStatistics {
     steps: i64,
     collisions: i64,
+    turns: i64,
     goal_reached: bool,
-    runs: i32
+    outcome: Option<RunOutcome>,
+    runs: i32,
+    unique_cells: usize,
+    revisits: u32,
+    time: u64,
+    sensor_reads: i64,
+    energy_used: u64,
+    wall_time: Duration,
+    control_time: Duration,
+    shortest_path: Option<usize>,
 }
 
 impl RobotStatistics {
@@ -234,6 +594,14 @@ impl RobotStatistics {
         self.collisions
     }
 
+    pub fn set_turns(&mut self, turns: i64) {
+        self.turns = turns
+    }
+
+    pub fn get_turns(&self) -> i64 {
+        self.turns
+    }
+
     pub fn set_goal_reached(&mut self, status: bool) {
         self.goal_reached = status
     }
@@ -242,6 +610,17 @@ impl RobotStatistics {
         self.goal_reached
     }
 
+    /// Record how the run ended
+    pub fn set_outcome(&mut self, outcome: RunOutcome) {
+        self.outcome = Some(outcome);
+        self.goal_reached = outcome == RunOutcome::ReachedGoal;
+    }
+
+    /// How the run ended, `None` while it is still going
+    pub fn get_outcome(&self) -> Option<RunOutcome> {
+        self.outcome
+    }
+
     pub fn set_run_number(&mut self, runs: i32) {
         self.runs = runs
     }
@@ -249,4 +628,107 @@ impl RobotStatistics {
     pub fn get_run_number(&self) -> i32 {
         self.runs
     }
+
+    pub fn set_unique_cells(&mut self, cells: usize) {
+        self.unique_cells = cells
+    }
+
+    pub fn get_unique_cells(&self) -> usize {
+        self.unique_cells
+    }
+
+    pub fn set_revisits(&mut self, revisits: u32) {
+        self.revisits = revisits
+    }
+
+    pub fn get_revisits(&self) -> u32 {
+        self.revisits
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time
+    }
+
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn set_sensor_reads(&mut self, reads: i64) {
+        self.sensor_reads = reads
+    }
+
+    pub fn get_sensor_reads(&self) -> i64 {
+        self.sensor_reads
+    }
+
+    pub fn set_energy_used(&mut self, energy: u64) {
+        self.energy_used = energy
+    }
+
+    pub fn get_energy_used(&self) -> u64 {
+        self.energy_used
+    }
+
+    /// Real time the run took, including any delay between moves
+    pub fn set_wall_time(&mut self, time: Duration) {
+        self.wall_time = time
+    }
+
+    pub fn get_wall_time(&self) -> Duration {
+        self.wall_time
+    }
+
+    /// Real time spent inside the controller's `control_robot`
+    pub fn set_control_time(&mut self, time: Duration) {
+        self.control_time = time
+    }
+
+    pub fn get_control_time(&self) -> Duration {
+        self.control_time
+    }
+
+    /// Add to the time spent inside the controller
+    pub fn add_control_time(&mut self, time: Duration) {
+        self.control_time += time
+    }
+
+    /// Fewest steps needed to reach the goal in this maze
+    pub fn set_shortest_path(&mut self, steps: Option<usize>) {
+        self.shortest_path = steps
+    }
+
+    pub fn get_shortest_path(&self) -> Option<usize> {
+        self.shortest_path
+    }
+
+    /// Shortest path length over the steps taken, 1.0 for a perfect run.
+    /// `None` unless the goal was reached.
+    pub fn get_efficiency(&self) -> Option<f64> {
+        if !self.goal_reached {
+            return None;
+        }
+        match (self.shortest_path, self.steps) {
+            (Some(_), 0) => Some(1.0),
+            (Some(shortest), steps) => Some(shortest as f64 / steps as f64),
+            (None, _) => None,
+        }
+    }
+
+    /// Fill in the counters from the robot's current state
+    pub fn record_robot<R: Robot>(&mut self, robot: &R) {
+        self.steps = robot.get_steps();
+        self.collisions = robot.get_collisions();
+        self.turns = robot.get_turns();
+        self.time = robot.get_time();
+        self.sensor_reads = robot.get_sensor_reads();
+        self.energy_used = robot.get_energy_used();
+        self.runs = robot.get_runs();
+        self.goal_reached = robot.at_goal();
+    }
+
+    /// Fill in the visit counts from a robot's trail
+    pub fn record_trail(&mut self, trail: &VisitTrail) {
+        self.unique_cells = trail.unique_cells();
+        self.revisits = trail.revisits();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Facing, Heading, Tile, TileType};
+
+/// Opt-in model of an imperfect robot. Each value is the chance of that
+/// fault happening on a single sensor read or move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseModel {
+    /// `look` reports a wall as a passage or a passage as a wall
+    pub look_error: f64,
+    /// `range` reports one cell more or less than it should
+    pub range_error: f64,
+    /// The robot doesn't move when it is advanced
+    pub stall: f64,
+    /// The robot moves to the left or right of its heading instead
+    pub drift: f64,
+    /// Seed for the random faults, so runs can be repeated
+    pub seed: u64,
+}
+
+impl Default for NoiseModel {
+    fn default() -> Self {
+        NoiseModel {
+            look_error: 0.05,
+            range_error: 0.1,
+            stall: 0.02,
+            drift: 0.02,
+            seed: 0,
+        }
+    }
+}
+
+/// A noise model along with the random state it is driven by
+#[derive(Debug)]
+pub(crate) struct Noise {
+    model: NoiseModel,
+    rng: RefCell<StdRng>,
+}
+
+impl Noise {
+    pub(crate) fn new(model: NoiseModel) -> Self {
+        Noise {
+            model,
+            rng: RefCell::new(StdRng::seed_from_u64(model.seed)),
+        }
+    }
+
+    fn happens(&self, chance: f64) -> bool {
+        chance > 0.0 && self.rng.borrow_mut().gen_bool(chance.min(1.0))
+    }
+
+    /// What the robot thinks it sees
+    pub(crate) fn look(&self, tile: Tile) -> Tile {
+        if !self.happens(self.model.look_error) {
+            tile
+        } else if tile.can_walk() {
+            Tile::Wall
+        } else {
+            Tile::Passage
+        }
+    }
+
+    /// What the range sensor reports
+    pub(crate) fn range(&self, range: usize) -> usize {
+        if !self.happens(self.model.range_error) {
+            range
+        } else if self.rng.borrow_mut().gen_bool(0.5) {
+            range + 1
+        } else {
+            range.saturating_sub(1)
+        }
+    }
+
+    /// The heading the robot actually moves in, `None` if it stalls
+    pub(crate) fn heading(&self, heading: Heading) -> Option<Heading> {
+        if self.happens(self.model.stall) {
+            None
+        } else if self.happens(self.model.drift) {
+            let side = if self.rng.borrow_mut().gen_bool(0.5) { Facing::Left } else { Facing::Right };
+            Some(heading.augment_heading(side))
+        } else {
+            Some(heading)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_faults() {
+        let model = NoiseModel { look_error: 0.5, range_error: 0.5, stall: 0.2, drift: 0.2, seed: 7 };
+        let (a, b) = (Noise::new(model), Noise::new(model));
+
+        for _ in 0..50 {
+            assert_eq!(a.look(Tile::Passage), b.look(Tile::Passage));
+            assert_eq!(a.range(3), b.range(3));
+            assert_eq!(a.heading(Heading::North), b.heading(Heading::North));
+        }
+
+        let always = Noise::new(NoiseModel { look_error: 1.0, ..model });
+        assert_eq!(always.look(Tile::Wall), Tile::Passage);
+        assert_eq!(always.look(Tile::Road), Tile::Wall);
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+
+use crate::Point;
+
+use super::{
+    trace::{RunTrace, TraceTick},
+    Heading, Robot, RobotStatistics, StepEvent,
+};
+
+/// Where and how a run is starting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunInfo {
+    /// Number of runs already finished since the controller was started
+    pub run: usize,
+    pub start: Point,
+    pub heading: Heading,
+}
+
+/// Watches a controller wrapper as it runs. Any number of observers can be
+/// added to a wrapper and each hook does nothing unless implemented.
+pub trait ExecutionObserver<R: Robot> {
+    /// A run is about to start
+    fn on_run_start(&mut self, _robot: &R, _info: &RunInfo) {}
+
+    /// The controller has been polled and the robot moved
+    fn on_tick(&mut self, _robot: &R, _tick: &TraceTick, _event: &StepEvent) {}
+
+    /// The robot bumped into a wall this tick
+    fn on_collision(&mut self, _robot: &R) {}
+
+    /// The robot has reached the goal
+    fn on_goal_reached(&mut self, _robot: &R) {}
+
+    /// A run has ended, with the statistics for the run
+    fn on_run_end(&mut self, _robot: &R, _statistics: &RobotStatistics) {}
+
+    /// The robot has been put back at the start for another run
+    fn on_reset(&mut self, _robot: &R) {}
+}
+
+/// Prints the robot and maze to the console after every tick
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsolePrinter;
+
+impl<R: Robot> ExecutionObserver<R> for ConsolePrinter {
+    fn on_tick(&mut self, robot: &R, _tick: &TraceTick, _event: &StepEvent) {
+        robot.print();
+    }
+
+    fn on_run_end(&mut self, _robot: &R, statistics: &RobotStatistics) {
+        println!(
+            "Run {} finished: {:?} in {} steps",
+            statistics.get_run_number(),
+            statistics.get_outcome(),
+            statistics.get_steps()
+        );
+    }
+}
+
+/// Calls a closure with the robot after every tick
+pub struct TickCallback<F> {
+    callback: F,
+}
+
+impl<F> TickCallback<F> {
+    pub fn new(callback: F) -> Self {
+        TickCallback { callback }
+    }
+}
+
+impl<R: Robot, F: FnMut(&R)> ExecutionObserver<R> for TickCallback<F> {
+    fn on_tick(&mut self, robot: &R, _tick: &TraceTick, _event: &StepEvent) {
+        (self.callback)(robot);
+    }
+}
+
+/// Keeps the statistics of every run it sees. Clones share the same
+/// statistics, so keep a clone to read them back after adding it to a wrapper.
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsCollector {
+    runs: Arc<Mutex<Vec<RobotStatistics>>>,
+}
+
+impl StatisticsCollector {
+    pub fn get_runs(&self) -> Vec<RobotStatistics> {
+        match self.runs.lock() {
+            Ok(runs) => runs.clone(),
+            Err(_) => unreachable!()
+        }
+    }
+}
+
+impl<R: Robot> ExecutionObserver<R> for StatisticsCollector {
+    fn on_run_end(&mut self, _robot: &R, statistics: &RobotStatistics) {
+        if let Ok(mut runs) = self.runs.lock() {
+            runs.push(statistics.clone());
+        }
+    }
+}
+
+/// Records a trace of every run it sees. Clones share the same traces,
+/// so keep a clone to read them back after adding it to a wrapper.
+#[derive(Debug, Clone, Default)]
+pub struct TraceRecorder {
+    traces: Arc<Mutex<Vec<RunTrace>>>,
+}
+
+impl TraceRecorder {
+    pub fn get_traces(&self) -> Vec<RunTrace> {
+        match self.traces.lock() {
+            Ok(traces) => traces.clone(),
+            Err(_) => unreachable!()
+        }
+    }
+}
+
+impl<R: Robot> ExecutionObserver<R> for TraceRecorder {
+    fn on_run_start(&mut self, _robot: &R, info: &RunInfo) {
+        if let Ok(mut traces) = self.traces.lock() {
+            traces.push(RunTrace::new(info.start, info.heading));
+        }
+    }
+
+    fn on_tick(&mut self, _robot: &R, tick: &TraceTick, _event: &StepEvent) {
+        if let Ok(mut traces) = self.traces.lock() {
+            if let Some(trace) = traces.last_mut() {
+                trace.push(*tick);
+            }
+        }
+    }
+
+    fn on_run_end(&mut self, _robot: &R, statistics: &RobotStatistics) {
+        if let (Ok(mut traces), Some(outcome)) = (self.traces.lock(), statistics.get_outcome()) {
+            if let Some(trace) = traces.last_mut() {
+                trace.finish(outcome);
+            }
+        }
+    }
+}
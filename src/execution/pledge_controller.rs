@@ -0,0 +1,103 @@
+use super::{
+    polled_controller::PolledController,
+    wall_follower_controller::Hand,
+    Facing, Robot, TileType,
+};
+
+/// The Pledge algorithm. Heads straight on in the way the robot starts
+/// facing, and when blocked follows the wall with one hand while counting
+/// its turns. It only leaves the wall once the turns cancel out and it is
+/// facing the starting way again, so unlike wall following it gets away
+/// from loops and free-standing walls. Only uses `look` and `face`.
+///
+/// Always escapes to the edge of a maze, but isn't sure to find a goal in
+/// the middle of one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PledgeController {
+    hand: Hand,
+    /// Quarter turns made while following the wall, positive towards the hand
+    turns: i32,
+    following: bool,
+}
+
+impl PledgeController {
+    pub fn new(hand: Hand) -> Self {
+        PledgeController { hand, ..Default::default() }
+    }
+
+    pub fn get_hand(&self) -> Hand {
+        self.hand
+    }
+
+    fn turn<R: Robot>(&mut self, robot: &mut R, face: Facing) {
+        self.turns += match face {
+            Facing::Ahead => 0,
+            Facing::Behind => -2,
+            face if face == self.hand.facing() => 1,
+            _ => -1,
+        };
+        robot.face(face);
+    }
+}
+
+impl<R: Robot> PolledController<R> for PledgeController {
+    fn control_robot(&mut self, robot: &mut R) {
+        if self.following && self.turns == 0 && robot.look(Facing::Ahead).can_walk() {
+            self.following = false;
+        }
+
+        if !self.following {
+            if robot.look(Facing::Ahead).can_walk() {
+                return;
+            }
+            // Put the wall on the hand side and start following it
+            self.following = true;
+            self.turn(robot, self.hand.other());
+        }
+
+        let face = [self.hand.facing(), Facing::Ahead, self.hand.other()]
+            .into_iter()
+            .find(|face| robot.look(*face).can_walk())
+            .unwrap_or(Facing::Behind);
+        self.turn(robot, face);
+    }
+
+    fn reset(&mut self) {
+        self.turns = 0;
+        self.following = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution::{polled_controller::PolledControllerWrapper, robot::DefaultRobot, sensors::SensorConfig, Controller, RunOutcome, Tile},
+        generation::Maze,
+        Point,
+    };
+
+    #[test]
+    fn gets_round_a_pillar() {
+        // Open room with a pillar in the way of the robot heading south
+        let mut maze: Maze<Tile> = Maze::new(11, 11);
+        for i in 0..11 {
+            for edge in [Point(i, 0), Point(i, 10), Point(0, i), Point(10, i)] {
+                maze.set_cell(edge, Tile::Wall);
+            }
+        }
+        for x in 4..7 {
+            for y in 4..7 {
+                maze.set_cell(Point(x, y), Tile::Wall);
+            }
+        }
+        maze.set_start(Point(5, 1));
+        maze.set_finish(Point(9, 9));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, PledgeController>::new();
+        wrapper.set_sensors(SensorConfig::blind());
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+        assert_eq!(wrapper.get_statistics().get_collisions(), 0);
+    }
+}
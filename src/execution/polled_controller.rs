@@ -1,44 +1,301 @@
-use super::{Controller, Robot, private, TileType};
+use std::time::Instant;
+
+use super::{energy::EnergyModel, limits::RunLimits, noise::NoiseModel, observer::{ExecutionObserver, RunInfo}, sensors::SensorConfig, statistics::RunSummary, trace::{RunTrace, TraceMismatch, TraceTick}, Controller, Robot, RobotStatistics, RunOutcome, StepEvent, private, tick_delay, TileType};
 
 use crate::execution::Maze;
 
 /// A Polled Controller is what most people want to be writing
 /// where the controller has a function which gets repeatedly called for each movement
 /// 
-/// Needs to implement default and can use that default method to intialise any data/structures before runing.
+/// Implement default to make the controller with `PolledControllerWrapper::new`, and use it to intialise any
+/// data/structures before runing. Controllers without a default can be given to `with_controller`, or see
+/// `ClosureController` to make one from a closure.
 /// control_robot: will be repeatedly called at each step. It should set the robot's facing direction, the controller wrapper
 /// will move the robot onto a new square.
-/// reset: will be called whenever the robot reaches the goal and gets reset to the starting position for another run. 
-pub trait PolledController<R: Robot>: Default {
+/// run_finished: will be called as each run ends, with how it ended.
+/// reset: will be called at the end of each run when the robot gets reset to the starting position for another run.
+/// The controller itself is kept between runs, so anything it has learnt can be used on the next one.
+pub trait PolledController<R: Robot> {
 
     fn control_robot(&mut self, robot: &mut R);
 
-    fn reset(&self) { }
+    fn run_finished(&mut self, _outcome: RunOutcome) { }
+
+    fn reset(&mut self) { }
 }
 
 /// The wrapper implements the normal functionality of the controller for the Polled Controller
 /// so we just need a PolledController struct to create this wrapping
-pub struct PolledControllerWrapper<R: Robot, P: PolledController<R> + Default>{
+pub struct PolledControllerWrapper<R: Robot, P: PolledController<R>>{
     controller: Box<P>,
     robot: R,
     active: bool,
     delay: i32,
-    callback: Box<dyn Fn(&R) -> ()>
+    callback: Box<dyn Fn(&R)>,
+    statistics: RobotStatistics,
+    limits: RunLimits,
+    runs: usize,
+    history: Vec<RobotStatistics>,
+    traces: Vec<RunTrace>,
+    observers: Vec<Box<dyn ExecutionObserver<R>>>,
+    /// Trace and start time of the run in progress
+    current_run: Option<(RunTrace, Instant)>,
+    /// Is there a set of runs in progress
+    in_session: bool,
+    /// Has the robot been run since it was put on the maze, so needs
+    /// putting back at the start before a new set of runs
+    robot_used: bool,
 }
 
-impl <R: Robot, P: PolledController<R>> PolledControllerWrapper<R, P> {
+impl<R: Robot, P: PolledController<R> + Default> Default for PolledControllerWrapper<R, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <R: Robot, P: PolledController<R> + Default> PolledControllerWrapper<R, P> {
     
     pub fn new() -> Self {
-        PolledControllerWrapper { controller: Box::new(Default::default()), robot: Default::default(), active: false, delay: 0, callback: Box::new(|_|{}) }
+        Self::with_controller(P::default())
     }
+}
+
+impl <R: Robot, P: PolledController<R>> PolledControllerWrapper<R, P> {
     
     pub fn with_controller(controller: P) -> Self {
-        PolledControllerWrapper {controller: Box::new(controller), robot: Default::default(), active: false, delay: 0, callback: Box::new(|_| {})}
+        PolledControllerWrapper {controller: Box::new(controller), robot: Default::default(), active: false, delay: 0, callback: Box::new(|_| {}), statistics: Default::default(), limits: RunLimits::default(), runs: 1, history: Vec::new(), traces: Vec::new(), current_run: None, in_session: false, robot_used: false, observers: Vec::new()}
     }
 
-    pub fn set_poll_callback(&mut self, cb: Box<dyn Fn(&R) -> ()>) {
+    /// Called with the robot after every tick, replacing any callback set before.
+    /// Observers added with `add_observer` get more detail about the run.
+    #[deprecated(note = "add a `TickCallback` with `add_observer` instead")]
+    pub fn set_poll_callback(&mut self, cb: Box<dyn Fn(&R)>) {
         self.callback = cb;
-    } 
+    }
+
+    /// Stop runs early once the robot has taken too many steps, hit too many
+    /// walls or taken too long
+    pub fn set_limits(&mut self, limits: RunLimits) {
+        self.limits = limits;
+    }
+
+    /// Number of runs to make each time the controller is started, at least one
+    pub fn set_runs(&mut self, runs: usize) {
+        self.runs = runs.max(1);
+    }
+
+    pub fn get_runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Statistics for the current (or last) run
+    pub fn get_statistics(&self) -> &RobotStatistics {
+        &self.statistics
+    }
+
+    /// Statistics for each run finished since the controller was last started
+    pub fn get_history(&self) -> &[RobotStatistics] {
+        &self.history
+    }
+
+    /// Watch the controller as it runs, alongside any observers already added
+    pub fn add_observer(&mut self, observer: Box<dyn ExecutionObserver<R>>) {
+        self.observers.push(observer);
+    }
+
+    /// Trace of each run finished since the controller was last started
+    pub fn get_traces(&self) -> &[RunTrace] {
+        &self.traces
+    }
+
+    /// Start the controller and check its first run matches a recorded trace.
+    /// Use a fresh controller on the same maze, with the same noise seed.
+    pub fn verify_trace(&mut self, expected: &RunTrace) -> Result<(), TraceMismatch>
+    where
+        Self: Controller<R, R::Tiles>,
+    {
+        self.start();
+        match self.traces.first() {
+            Some(actual) => expected.verify(actual),
+            None => Err(TraceMismatch::Start),
+        }
+    }
+
+    /// Summary of every run finished since the controller was last started
+    pub fn get_summary(&self) -> RunSummary {
+        RunSummary::from_runs(&self.history)
+    }
+}
+
+// Robots can only be made in this crate, so the bound doesn't stop anyone using these
+#[allow(private_bounds)]
+impl <R: Robot + private::Robot, P: PolledController<R>> PolledControllerWrapper<R, P> {
+    /// Limit how far the robot's range sensor can see, `None` for no limit
+    pub fn set_range_limit(&mut self, limit: Option<usize>) {
+        self.robot.set_range_limit(limit);
+    }
+
+    /// Make the robot's sensors and movement unreliable, `None` for a perfect robot
+    pub fn set_noise(&mut self, noise: Option<NoiseModel>) {
+        self.robot.set_noise(noise);
+    }
+
+    /// Give the robot a battery which its actions drain, `None` for unlimited energy.
+    /// The battery is recharged at the start of each run.
+    pub fn set_energy(&mut self, energy: Option<EnergyModel>) {
+        self.robot.set_energy(energy);
+    }
+
+    /// Choose which sensors the controller is allowed to use
+    pub fn set_sensors(&mut self, sensors: SensorConfig) {
+        self.robot.set_sensors(sensors);
+    }
+
+    /// Only draw what the robot has discovered when it is printed
+    pub fn set_fog_of_war(&mut self, fog: bool) {
+        self.robot.set_fog_of_war(fog);
+    }
+
+    /// Run a single control and move cycle, starting the next run first if
+    /// one isn't already going. Says what happened to the robot.
+    pub fn step(&mut self) -> StepEvent {
+        if self.current_run.is_none() {
+            self.begin_run();
+        }
+        if let Some(outcome) = self.finished() {
+            return self.finish_run(outcome);
+        }
+
+        let (reads, collisions, steps) = (self.robot.get_sensor_reads(), self.robot.get_collisions(), self.robot.get_steps());
+        let control_started = Instant::now();
+        self.controller.control_robot(&mut self.robot);
+        self.statistics.add_control_time(control_started.elapsed());
+
+        let time = self.robot.get_time();
+        self.robot.advance(); // ??: Not sure the robot obj should be calling "advance"
+        self.statistics.record_robot(&self.robot);
+
+        let position = private::Robot::current_location(&self.robot);
+        let collided = self.robot.get_collisions() > collisions;
+        let tick = TraceTick {
+            heading: private::Robot::current_heading(&self.robot),
+            sensor_reads: self.robot.get_sensor_reads() - reads,
+            position,
+            collided,
+        };
+        if let Some((trace, _)) = &mut self.current_run {
+            trace.push(tick);
+        }
+
+        (self.callback)(&self.robot);
+
+        if self.delay > 0 {
+            // Slower tiles take proportionally longer to cross
+            self.robot.sleep(tick_delay(self.delay, self.robot.get_time().saturating_sub(time)));
+        }
+
+        let event = if let Some(outcome) = self.finished() {
+            StepEvent::Finished(outcome)
+        } else if collided {
+            StepEvent::Collided
+        } else if self.robot.get_steps() > steps {
+            StepEvent::Moved(position)
+        } else {
+            StepEvent::Stalled
+        };
+
+        for observer in &mut self.observers {
+            observer.on_tick(&self.robot, &tick, &event);
+            if collided {
+                observer.on_collision(&self.robot);
+            }
+        }
+
+        match event {
+            StepEvent::Finished(outcome) => self.finish_run(outcome),
+            event => event,
+        }
+    }
+
+    /// Keep stepping until `until` returns true for a step, or every run has finished.
+    /// Gives the last step's event.
+    pub fn run_until(&mut self, mut until: impl FnMut(&StepEvent, &R) -> bool) -> StepEvent {
+        loop {
+            let event = self.step();
+            if until(&event, &self.robot) || (matches!(event, StepEvent::Finished(_)) && !self.in_session) {
+                return event;
+            }
+        }
+    }
+
+    /// Get ready for the next run, starting a new set of runs if needed
+    fn begin_run(&mut self) {
+        if !self.in_session {
+            if self.robot_used {
+                self.robot.reset();
+            }
+            self.in_session = true;
+            self.active = true;
+            self.history.clear();
+            self.traces.clear();
+        } else {
+            self.robot.reset();
+            self.controller.reset();
+            for observer in &mut self.observers {
+                observer.on_reset(&self.robot);
+            }
+        }
+
+        self.statistics = RobotStatistics::default();
+        self.statistics.record_robot(&self.robot);
+        self.statistics.set_shortest_path(private::Robot::shortest_path(&self.robot));
+
+        let info = RunInfo {
+            run: self.history.len(),
+            start: private::Robot::current_location(&self.robot),
+            heading: private::Robot::current_heading(&self.robot),
+        };
+        for observer in &mut self.observers {
+            observer.on_run_start(&self.robot, &info);
+        }
+
+        self.current_run = Some((RunTrace::new(info.start, info.heading), Instant::now()));
+        self.robot_used = true;
+    }
+
+    /// How the current run has ended, if it has
+    fn finished(&self) -> Option<RunOutcome> {
+        let started = self.current_run.as_ref().map_or_else(Instant::now, |(_, started)| *started);
+        RunOutcome::from_robot(&self.robot)
+            .or_else(|| self.limits.check(&self.robot, started))
+            .or_else(|| (!self.active).then_some(RunOutcome::Aborted))
+    }
+
+    /// Record the end of the current run
+    fn finish_run(&mut self, outcome: RunOutcome) -> StepEvent {
+        if let Some((mut trace, started)) = self.current_run.take() {
+            self.statistics.record_trail(&self.robot.get_trail());
+            self.statistics.set_wall_time(started.elapsed());
+            self.statistics.set_outcome(outcome);
+            self.history.push(self.statistics.clone());
+            trace.finish(outcome);
+            self.traces.push(trace);
+            self.controller.run_finished(outcome);
+
+            for observer in &mut self.observers {
+                if outcome == RunOutcome::ReachedGoal {
+                    observer.on_goal_reached(&self.robot);
+                }
+                observer.on_run_end(&self.robot, &self.statistics);
+            }
+        }
+
+        if outcome == RunOutcome::Aborted || self.history.len() >= self.runs {
+            self.in_session = false;
+        }
+
+        StepEvent::Finished(outcome)
+    }
 }
 
 impl <R: Robot<Tiles=K> + private::Robot, P: PolledController<R>, K: TileType + Default> Controller<R, K> for PolledControllerWrapper<R, P> {
@@ -46,6 +303,7 @@ impl <R: Robot<Tiles=K> + private::Robot, P: PolledController<R>, K: TileType +
     /// Set the robot the controller operates on
     fn set_robot(&mut self, robot: R) {
         self.robot = robot;
+        self.robot_used = false;
     }
 
     fn get_robot(&self) -> &R {
@@ -55,27 +313,20 @@ impl <R: Robot<Tiles=K> + private::Robot, P: PolledController<R>, K: TileType +
     fn set_maze(&mut self, maze: Maze<K>) {
         let maze = Box::new(maze);
         self.robot.set_maze(maze);
+        self.robot_used = false;
+        self.in_session = false;
+        self.current_run = None;
     }
 
     /// Called when the controller is started
-    fn start(&mut self) {
-        self.active = true;
-
-        while !self.robot.get_location().eq(&self.robot.get_goal_location()) && self.active {
+    fn start(&mut self) -> RunOutcome {
+        self.in_session = false;
+        self.current_run = None;
 
-            self.controller.control_robot(&mut self.robot);
-            
-            self.robot.advance(); // ??: Not sure the robot obj should be calling "advance"
-
-            (self.callback)(&mut self.robot);
-
-            if self.delay > 0 {
-                self.robot.sleep(self.delay);
-            }
+        match self.run_until(|_, _| false) {
+            StepEvent::Finished(outcome) => outcome,
+            _ => unreachable!(),
         }
-
-        println!("Robot reached goal. ");
-        // todo!("Reset & Active check in end of start");
     }
 
     /// Called when the controller is reset
@@ -102,4 +353,286 @@ impl <R: Robot<Tiles=K> + private::Robot, P: PolledController<R>, K: TileType +
     fn get_description(&self) -> &str {
         "A Polled Robot Controller"
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+    use crate::execution::{observer::{StatisticsCollector, TraceRecorder}, robot::DefaultRobot, sensors::SensorError, trace::ReplayController, wall_follower_controller::WallFollowerController, Heading, Tile};
+    use crate::Point;
+
+    #[derive(Default)]
+    struct EastController;
+
+    impl<R: Robot> PolledController<R> for EastController {
+        fn control_robot(&mut self, robot: &mut R) {
+            robot.set_heading(Heading::East).unwrap();
+        }
+    }
+
+    #[test]
+    fn start_reports_outcome() {
+        let mut maze: Maze<Tile> = Maze::new(3, 1);
+        maze.set_finish(Point(2, 0));
+        maze.set_cell(Point(1, 0), Tile::Pit);
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        wrapper.set_maze(maze.clone());
+        assert_eq!(wrapper.start(), RunOutcome::Destroyed);
+        assert_eq!(wrapper.get_statistics().get_outcome(), Some(RunOutcome::Destroyed));
+
+        maze.set_cell(Point(1, 0), Tile::Passage);
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+        assert!(wrapper.get_statistics().goal_reached());
+    }
+
+    #[test]
+    fn settings_apply_to_robot_straight_away() {
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        wrapper.set_maze(Maze::new(3, 1));
+        wrapper.set_sensors(SensorConfig::blind());
+        assert_eq!(wrapper.get_robot().get_location(), Err(SensorError::NoPosition));
+
+        // And they are kept when the maze changes
+        wrapper.set_maze(Maze::new(4, 1));
+        assert_eq!(wrapper.get_robot().get_location(), Err(SensorError::NoPosition));
+    }
+
+    #[test]
+    fn delay_is_per_passage() {
+        assert_eq!(tick_delay(100, 2), 100);
+        assert_eq!(tick_delay(100, 5), 250);
+        assert_eq!(tick_delay(100, 1), 50);
+        assert_eq!(tick_delay(100, 0), 50);
+        assert_eq!(tick_delay(i32::MAX, u64::MAX), i32::MAX);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn poll_callback_sees_every_tick() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+        let ticks = Rc::new(RefCell::new(0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        let counter = ticks.clone();
+        wrapper.set_poll_callback(Box::new(move |_| *counter.borrow_mut() += 1));
+        wrapper.set_maze(maze);
+        wrapper.start();
+        assert_eq!(*ticks.borrow(), 3);
+    }
+
+    #[test]
+    fn flat_battery_ends_run() {
+        let mut maze: Maze<Tile> = Maze::new(5, 1);
+        maze.set_finish(Point(4, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        wrapper.set_energy(Some(EnergyModel { capacity: 3, ..Default::default() }));
+        wrapper.set_maze(maze);
+
+        // A quarter turn to face east then two ticks crossing a passage
+        assert_eq!(wrapper.start(), RunOutcome::OutOfEnergy);
+        assert_eq!(wrapper.get_statistics().get_energy_used(), 3);
+        assert_eq!(wrapper.get_statistics().get_steps(), 1);
+    }
+
+    #[test]
+    fn limits_end_run() {
+        let mut maze: Maze<Tile> = Maze::new(6, 1);
+        maze.set_finish(Point(5, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        wrapper.set_limits(RunLimits { max_steps: Some(2), ..Default::default() });
+        wrapper.set_maze(maze.clone());
+        assert_eq!(wrapper.start(), RunOutcome::OutOfSteps);
+        assert_eq!(wrapper.get_statistics().get_steps(), 2);
+
+        maze.set_cell(Point(1, 0), Tile::Wall);
+        wrapper.set_limits(RunLimits { max_collisions: Some(3), ..Default::default() });
+        wrapper.set_maze(maze.clone());
+        assert_eq!(wrapper.start(), RunOutcome::TooManyCollisions);
+        assert_eq!(wrapper.get_statistics().get_collisions(), 3);
+
+        wrapper.set_limits(RunLimits { timeout: Some(Duration::ZERO), ..Default::default() });
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::TimedOut);
+    }
+
+    /// Bumps into the wall behind it on the first run only
+    #[derive(Default)]
+    struct LearningController {
+        runs: usize,
+        bumped: bool,
+    }
+
+    impl<R: Robot> PolledController<R> for LearningController {
+        fn control_robot(&mut self, robot: &mut R) {
+            let heading = if self.runs == 0 && !self.bumped { Heading::West } else { Heading::East };
+            self.bumped = true;
+            robot.set_heading(heading).unwrap();
+        }
+
+        fn reset(&mut self) {
+            self.runs += 1;
+        }
+    }
+
+    #[test]
+    fn runs_several_times_keeping_controller() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, LearningController>::new();
+        wrapper.set_runs(3);
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+
+        let history = wrapper.get_history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.iter().map(|run| run.get_collisions()).collect::<Vec<_>>(), vec![1, 0, 0]);
+        assert!(history.iter().all(|run| run.get_steps() == 3 && run.goal_reached()));
+        assert_eq!(history[2].get_run_number(), 2);
+        assert_eq!(wrapper.get_robot().get_runs(), 2);
+
+        assert_eq!(history.iter().map(|run| run.get_turns()).collect::<Vec<_>>(), vec![2, 1, 1]);
+        let summary = wrapper.get_summary();
+        assert_eq!(summary.get_goals_reached(), 3);
+        assert_eq!(summary.get_efficiency().map(|efficiency| efficiency.best), Some(1.0));
+    }
+
+    #[test]
+    fn traces_can_be_verified_and_replayed() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, LearningController>::new();
+        wrapper.set_maze(maze.clone());
+        wrapper.start();
+        let trace = wrapper.get_traces()[0].clone();
+        assert_eq!(trace.get_ticks().len(), 4);
+        assert!(trace.get_ticks()[0].collided);
+
+        let mut fresh = PolledControllerWrapper::<DefaultRobot, LearningController>::new();
+        fresh.set_maze(maze.clone());
+        assert_eq!(fresh.verify_trace(&trace), Ok(()));
+
+        let mut replay = PolledControllerWrapper::<DefaultRobot, _>::with_controller(ReplayController::new(&trace));
+        replay.set_maze(maze);
+        replay.start();
+        assert_eq!(trace.verify_replay(&replay.get_traces()[0]), Ok(()));
+    }
+
+    #[test]
+    fn replays_every_run_of_sensing_controller() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, WallFollowerController>::new();
+        wrapper.set_runs(2);
+        wrapper.set_maze(maze.clone());
+        wrapper.start();
+        let trace = wrapper.get_traces()[0].clone();
+        assert!(trace.get_ticks().iter().any(|tick| tick.sensor_reads > 0));
+
+        let mut replay = PolledControllerWrapper::<DefaultRobot, _>::with_controller(ReplayController::new(&trace));
+        replay.set_runs(2);
+        replay.set_limits(RunLimits { max_steps: Some(20), max_collisions: Some(20), ..Default::default() });
+        replay.set_maze(maze);
+        assert_eq!(replay.start(), RunOutcome::ReachedGoal);
+        assert_eq!(replay.get_traces().len(), 2);
+        for actual in replay.get_traces() {
+            assert_eq!(trace.verify_replay(actual), Ok(()));
+        }
+        assert!(trace.verify(&replay.get_traces()[0]).is_err());
+    }
+
+    #[test]
+    fn can_be_stepped() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, LearningController>::new();
+        wrapper.set_runs(2);
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.step(), StepEvent::Collided);
+        assert_eq!(wrapper.step(), StepEvent::Moved(Point(1, 0)));
+
+        let event = wrapper.run_until(|event, _| matches!(event, StepEvent::Finished(_)));
+        assert_eq!(event, StepEvent::Finished(RunOutcome::ReachedGoal));
+        assert_eq!(wrapper.get_history().len(), 1);
+
+        assert_eq!(wrapper.step(), StepEvent::Moved(Point(1, 0)));
+        assert_eq!(wrapper.run_until(|_, _| false), StepEvent::Finished(RunOutcome::ReachedGoal));
+        assert_eq!(wrapper.get_history().len(), 2);
+    }
+
+    #[test]
+    fn each_start_makes_a_new_run() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, EastController>::new();
+        wrapper.set_maze(maze);
+        for _ in 0..2 {
+            assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+            assert_eq!(wrapper.get_history().len(), 1);
+            assert_eq!(wrapper.get_statistics().get_steps(), 3);
+        }
+
+        // Stepping after the runs have finished starts another from the beginning
+        assert_eq!(wrapper.step(), StepEvent::Moved(Point(1, 0)));
+        assert_eq!(wrapper.run_until(|_, _| false), StepEvent::Finished(RunOutcome::ReachedGoal));
+        assert_eq!(wrapper.get_statistics().get_steps(), 3);
+        assert_eq!(wrapper.get_robot().get_runs(), 2);
+    }
+
+    #[derive(Default)]
+    struct EventCounter(Rc<RefCell<Vec<&'static str>>>);
+
+    impl<R: Robot> ExecutionObserver<R> for EventCounter {
+        fn on_run_start(&mut self, _robot: &R, _info: &RunInfo) {
+            self.0.borrow_mut().push("start");
+        }
+
+        fn on_collision(&mut self, _robot: &R) {
+            self.0.borrow_mut().push("collision");
+        }
+
+        fn on_goal_reached(&mut self, _robot: &R) {
+            self.0.borrow_mut().push("goal");
+        }
+
+        fn on_run_end(&mut self, _robot: &R, _statistics: &RobotStatistics) {
+            self.0.borrow_mut().push("end");
+        }
+
+        fn on_reset(&mut self, _robot: &R) {
+            self.0.borrow_mut().push("reset");
+        }
+    }
+
+    #[test]
+    fn observers_see_every_run() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let statistics = StatisticsCollector::default();
+        let traces = TraceRecorder::default();
+
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, LearningController>::new();
+        wrapper.add_observer(Box::new(EventCounter(events.clone())));
+        wrapper.add_observer(Box::new(statistics.clone()));
+        wrapper.add_observer(Box::new(traces.clone()));
+        wrapper.set_runs(2);
+        wrapper.set_maze(maze);
+        wrapper.start();
+
+        assert_eq!(*events.borrow(), ["start", "collision", "goal", "end", "reset", "start", "goal", "end"]);
+        assert_eq!(statistics.get_runs().len(), 2);
+        assert_eq!(statistics.get_runs()[1].get_steps(), wrapper.get_history()[1].get_steps());
+        assert_eq!(traces.get_traces(), wrapper.get_traces());
+    }
+}
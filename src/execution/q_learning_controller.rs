@@ -0,0 +1,342 @@
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::Point;
+
+use super::{
+    polled_controller::PolledController,
+    trace::{heading_symbol, parse_heading},
+    Facing, Heading, Robot, RunOutcome,
+};
+
+/// Actions the controller picks between, in the order their values are kept
+const ACTIONS: [Facing; 4] = [Facing::Ahead, Facing::Right, Facing::Behind, Facing::Left];
+
+/// How often to explore by picking a random action rather than the best one.
+/// Starts at `start` and is multiplied by `decay` after each run, down to `min`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpsilonSchedule {
+    pub start: f64,
+    pub decay: f64,
+    pub min: f64,
+}
+
+impl Default for EpsilonSchedule {
+    fn default() -> Self {
+        EpsilonSchedule {
+            start: 1.0,
+            decay: 0.95,
+            min: 0.05,
+        }
+    }
+}
+
+impl EpsilonSchedule {
+    /// Epsilon on a run, counting from zero
+    pub fn at(&self, run: usize) -> f64 {
+        (self.start * self.decay.powi(run as i32)).max(self.min).clamp(0.0, 1.0)
+    }
+}
+
+/// Settings for a [`QLearningController`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QLearningConfig {
+    /// How far each update moves a value towards the new estimate, alpha
+    pub learning_rate: f64,
+    /// How much rewards further on count, gamma
+    pub discount: f64,
+    pub epsilon: EpsilonSchedule,
+    /// Reward for each move which doesn't hit a wall
+    pub step_reward: f64,
+    pub collision_reward: f64,
+    pub goal_reward: f64,
+    /// Reward when a run ends without reaching the goal, such as falling
+    /// into a pit or running out of steps or energy
+    pub failure_reward: f64,
+    /// Seed for the random exploring so runs can be repeated, random if `None`
+    pub seed: Option<u64>,
+}
+
+impl Default for QLearningConfig {
+    fn default() -> Self {
+        QLearningConfig {
+            learning_rate: 0.5,
+            discount: 0.95,
+            epsilon: EpsilonSchedule::default(),
+            step_reward: -1.0,
+            collision_reward: -10.0,
+            goal_reward: 100.0,
+            failure_reward: -100.0,
+            seed: None,
+        }
+    }
+}
+
+/// Value of each action in each state, where a state is the robot's
+/// position and heading.
+///
+/// Saved as plain text, one state per line:
+/// ```text
+/// maze-qtable 1
+/// 1 1 E 2.5 -1 -0.5 0
+/// ```
+/// Each line is the position, heading and the values of turning ahead,
+/// right, behind and left.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QTable {
+    values: HashMap<(Point, Heading), [f64; 4]>,
+}
+
+impl QTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Values of turning ahead, right, behind and left, all zero for a state not seen yet
+    pub fn get(&self, position: Point, heading: Heading) -> [f64; 4] {
+        self.values.get(&(position, heading)).copied().unwrap_or_default()
+    }
+
+    /// The best action in a state, the first of any tied
+    pub fn best(&self, position: Point, heading: Heading) -> Facing {
+        ACTIONS[Self::best_index(&self.get(position, heading))]
+    }
+
+    /// Number of states which have values
+    pub fn states(&self) -> usize {
+        self.values.len()
+    }
+
+    fn best_index(values: &[f64; 4]) -> usize {
+        (1..values.len()).fold(0, |best, action| if values[action] > values[best] { action } else { best })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let bad_line = |number: usize| io::Error::new(io::ErrorKind::InvalidData, format!("couldn't read line {} of the Q-table", number + 1));
+
+        let mut lines = text.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty());
+        if lines.next().map(|(_, line)| line) != Some("maze-qtable 1") {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a Q-table"));
+        }
+
+        let mut table = QTable::new();
+        for (number, line) in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [x, y, heading, values @ ..] = &fields[..] else {
+                return Err(bad_line(number));
+            };
+            let position = match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Point(x, y),
+                _ => return Err(bad_line(number)),
+            };
+            let heading = parse_heading(heading).ok_or_else(|| bad_line(number))?;
+            let values: Vec<f64> = values.iter().map(|value| value.parse()).collect::<Result<_, _>>().map_err(|_| bad_line(number))?;
+            let values: [f64; 4] = values.try_into().map_err(|_| bad_line(number))?;
+            table.values.insert((position, heading), values);
+        }
+        Ok(table)
+    }
+}
+
+impl fmt::Display for QTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "maze-qtable 1")?;
+        let mut states: Vec<_> = self.values.iter().collect();
+        states.sort_by_key(|((position, heading), _)| (*position, *heading as u32));
+        for ((position, heading), values) in states {
+            write!(f, "{} {} {}", position.get_x(), position.get_y(), heading_symbol(*heading))?;
+            for value in values {
+                write!(f, " {}", value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tabular Q-learning. Learns the value of turning each way in every
+/// position and heading from the rewards it gets for moving, hitting walls
+/// and reaching the goal, picking the best action or exploring at random
+/// as set by the epsilon schedule.
+///
+/// The Q-table is kept between runs so the robot gets better each run, and
+/// can be saved and loaded to carry on learning later. Pass the wrapper's
+/// history to [`LearningCurve::from_runs`](super::statistics::LearningCurve::from_runs)
+/// to see how it improved.
+///
+/// Needs the position and compass sensors.
+#[derive(Debug, Clone)]
+pub struct QLearningController {
+    config: QLearningConfig,
+    table: QTable,
+    rng: StdRng,
+    /// Runs finished so far, for the epsilon schedule
+    runs: usize,
+    /// Last state and action taken, with the collisions before it, waiting to see what it led to
+    pending: Option<(Point, Heading, usize, i64)>,
+}
+
+impl Default for QLearningController {
+    fn default() -> Self {
+        Self::new(QLearningConfig::default())
+    }
+}
+
+impl QLearningController {
+    pub fn new(config: QLearningConfig) -> Self {
+        QLearningController {
+            config,
+            table: QTable::new(),
+            rng: match config.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            runs: 0,
+            pending: None,
+        }
+    }
+
+    pub fn get_config(&self) -> &QLearningConfig {
+        &self.config
+    }
+
+    pub fn get_table(&self) -> &QTable {
+        &self.table
+    }
+
+    /// Carry on learning from a table, such as one loaded from disk
+    pub fn set_table(&mut self, table: QTable) {
+        self.table = table;
+    }
+
+    /// Chance of exploring on the current run
+    pub fn get_epsilon(&self) -> f64 {
+        self.config.epsilon.at(self.runs)
+    }
+
+    /// Move the value of taking `action` in a state towards the reward
+    /// plus the discounted value of the best action in the next state
+    fn learn(&mut self, state: (Point, Heading), action: usize, reward: f64, next: Option<(Point, Heading)>) {
+        let future = next.map_or(0.0, |(position, heading)| {
+            self.table.get(position, heading).into_iter().fold(f64::NEG_INFINITY, f64::max)
+        });
+        let QLearningConfig { learning_rate, discount, .. } = self.config;
+        let value = &mut self.table.values.entry(state).or_default()[action];
+        *value += learning_rate * (reward + discount * future - *value);
+    }
+}
+
+impl<R: Robot> PolledController<R> for QLearningController {
+    fn control_robot(&mut self, robot: &mut R) {
+        let (Ok(position), Ok(heading)) = (robot.get_location(), robot.get_heading()) else {
+            return;
+        };
+        let collisions = robot.get_collisions();
+
+        if let Some((last_position, last_heading, action, last_collisions)) = self.pending.take() {
+            let reward = if collisions > last_collisions { self.config.collision_reward } else { self.config.step_reward };
+            self.learn((last_position, last_heading), action, reward, Some((position, heading)));
+        }
+
+        let action = if self.rng.gen_bool(self.get_epsilon()) {
+            self.rng.gen_range(0..ACTIONS.len())
+        } else {
+            QTable::best_index(&self.table.get(position, heading))
+        };
+        robot.face(ACTIONS[action]);
+        self.pending = Some((position, heading, action, collisions));
+    }
+
+    /// The last action ended the run, so learn from how it ended. Runs
+    /// stopped from outside teach it nothing.
+    fn run_finished(&mut self, outcome: RunOutcome) {
+        if let Some((position, heading, action, _)) = self.pending.take() {
+            let reward = match outcome {
+                RunOutcome::ReachedGoal => self.config.goal_reward,
+                RunOutcome::Aborted => return,
+                _ => self.config.failure_reward,
+            };
+            self.learn((position, heading), action, reward, None);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.runs += 1;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution::{
+            limits::RunLimits, polled_controller::PolledControllerWrapper, robot::DefaultRobot, statistics::LearningCurve,
+            Controller, RunOutcome, Tile,
+        },
+        generation::Maze,
+    };
+
+    #[test]
+    fn learns_shortest_route() {
+        // Open 3x3 room inside a wall
+        let mut maze: Maze<Tile> = Maze::new(5, 5);
+        for i in 0..5 {
+            for edge in [Point(i, 0), Point(i, 4), Point(0, i), Point(4, i)] {
+                maze.set_cell(edge, Tile::Wall);
+            }
+        }
+        maze.set_start(Point(1, 1));
+        maze.set_finish(Point(3, 3));
+
+        let config = QLearningConfig {
+            epsilon: EpsilonSchedule { start: 1.0, decay: 0.9, min: 0.0 },
+            seed: Some(7),
+            ..Default::default()
+        };
+        let mut wrapper = PolledControllerWrapper::<DefaultRobot, _>::with_controller(QLearningController::new(config));
+        wrapper.set_runs(80);
+        wrapper.set_limits(RunLimits { max_steps: Some(500), ..Default::default() });
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+
+        let curve = LearningCurve::from_runs(wrapper.get_history());
+        let averages = curve.moving_average(10);
+        assert!(averages[averages.len() - 1] < averages[9]);
+        assert_eq!(curve.get_steps().last(), Some(&4));
+        assert_eq!(wrapper.get_robot().get_sensor_reads(), 0);
+    }
+
+    #[test]
+    fn failed_runs_are_penalised() {
+        let mut controller = QLearningController { pending: Some((Point(1, 1), Heading::East, 0, 0)), ..Default::default() };
+        PolledController::<DefaultRobot>::run_finished(&mut controller, RunOutcome::Aborted);
+        assert_eq!(controller.get_table().get(Point(1, 1), Heading::East), [0.0; 4]);
+
+        controller.pending = Some((Point(1, 1), Heading::East, 0, 0));
+        PolledController::<DefaultRobot>::run_finished(&mut controller, RunOutcome::Destroyed);
+        assert!(controller.get_table().get(Point(1, 1), Heading::East)[0] < 0.0);
+        assert_eq!(controller.pending, None);
+    }
+
+    #[test]
+    fn table_saves_and_loads() {
+        let mut controller = QLearningController::default();
+        controller.learn((Point(1, 1), Heading::East), 1, 2.5, None);
+        controller.learn((Point(2, 1), Heading::North), 0, -1.0, Some((Point(1, 1), Heading::East)));
+
+        let path = std::env::temp_dir().join(format!("maze-qtable-{}.txt", std::process::id()));
+        controller.get_table().save(&path).unwrap();
+        let loaded = QTable::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(&loaded, controller.get_table());
+        assert_eq!(loaded.best(Point(1, 1), Heading::East), Facing::Right);
+    }
+}
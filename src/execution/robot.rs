@@ -1,19 +1,56 @@
-use crate::{generation::Maze, Point};
-use std::{borrow::{Borrow}, thread, time::Duration};
+use crate::{generation::Maze, solving::{path_solver::ShortestPathSolver, Solver}, Point};
+use std::{borrow::{Borrow}, cell::{Cell, RefCell}, thread, time::Duration};
 
-use super::{private, Facing, Heading, Robot, Tile};
+use super::{
+    map::{MapCell, RobotMap},
+    energy::{Battery, EnergyModel},
+    noise::{Noise, NoiseModel},
+    private,
+    rules::{self, Inventory, Move, RunState},
+    sensors::{self, LocalView, MazeRef, SensorConfig, SensorError},
+    trail::VisitTrail,
+    Facing, Heading, Robot, Tile, TileType, ROBOT_HEALTH, START_HEADING,
+};
 
 pub struct DefaultRobot {
     active: bool,
     location: Point,
     target: Point,
     maze: Box<Maze<Tile>>,
+    trail: VisitTrail,
+    map: RefCell<RobotMap>,
+    fog_of_war: bool,
+    state: RunState,
     heading: Heading,
     steps: i64,
     collisions: i64,
+    turns: i64,
+    time: u64,
+    health: u32,
+    sensor_reads: Cell<i64>,
+    range_limit: Option<usize>,
+    noise: Option<Noise>,
+    battery: Battery,
+    sensors: SensorConfig,
+    shortest_path: Option<usize>,
     runs: i32,
 }
 
+impl DefaultRobot {
+    /// Clear the counters and anything picked up for a fresh run
+    fn reset_run_state(&mut self) {
+        self.steps = 0;
+        self.collisions = 0;
+        self.turns = 0;
+        self.time = 0;
+        self.state = RunState::default();
+        self.health = ROBOT_HEALTH;
+        self.heading = START_HEADING;
+        self.sensor_reads.set(0);
+        self.battery.recharge();
+    }
+}
+
 impl Default for DefaultRobot {
     fn default() -> Self {
         DefaultRobot {
@@ -21,9 +58,22 @@ impl Default for DefaultRobot {
             location: Point(1, 1),
             target: Point(0, 0),
             maze: Box::new(Maze::new(0, 0)),
-            heading: Heading::South,
+            trail: VisitTrail::default(),
+            map: RefCell::default(),
+            fog_of_war: false,
+            state: RunState::default(),
+            heading: START_HEADING,
             steps: 0,
             collisions: 0,
+            turns: 0,
+            time: 0,
+            health: ROBOT_HEALTH,
+            sensor_reads: Cell::new(0),
+            range_limit: None,
+            noise: None,
+            battery: Battery::default(),
+            sensors: SensorConfig::default(),
+            shortest_path: None,
             runs: 0,
         }
     }
@@ -35,45 +85,109 @@ impl Robot for DefaultRobot {
     /// Look at the relative facing direction and get the tile
     fn look(&self, face: Facing) -> Self::Tiles {
         let heading = self.heading.augment_heading(face);
-        let Point(locx, locy) = self.get_location();
-        let pos = match heading { // Panic waiting to happen with these subs if the robot tries to look off the maze
-            Heading::North => Point(locx, locy - 1),
-            Heading::East => Point(locx + 1, locy),
-            Heading::South => Point(locx, locy + 1),
-            Heading::West => Point(locx - 1, locy),
+        self.sensor_reads.set(self.sensor_reads.get() + 1);
+        self.battery.sense();
+
+        let tile = heading
+            .next_point(self.location)
+            .and_then(|pos| self.maze.get_cell(pos))
+            .map_or(Tile::Wall, |tile| self.state.effective_tile(*tile));
+
+        let tile = match &self.noise {
+            Some(noise) => noise.look(tile),
+            None => tile,
         };
+        self.map.borrow_mut().record_look(self.location, heading, &tile);
+        tile
+    }
 
-        *self.get_maze().get_cell(pos).unwrap_or(&Tile::Wall)
+    /// Count the open cells in the relative facing direction
+    fn range(&self, face: Facing) -> usize {
+        let heading = self.heading.augment_heading(face);
+        self.sensor_reads.set(self.sensor_reads.get() + 1);
+        self.battery.sense();
+
+        let range = rules::range(&self.maze, &self.state, self.location, heading, self.range_limit);
+        let range = match &self.noise {
+            Some(noise) => noise.range(range),
+            None => range,
+        };
+        self.map.borrow_mut().record_range(self.location, heading, range, self.range_limit);
+        range
     }
 
     /// Change the robot to face a certain way
     fn face(&mut self, face: Facing) {
-        self.set_heading(self.heading.augment_heading(face))
+        self.battery.face(face);
+        if face != Facing::Ahead {
+            self.turns += 1;
+        }
+        self.heading = self.heading.augment_heading(face)
     }
 
     /// Set the robots absolute heading
-    fn set_heading(&mut self, heading: Heading) {
-        self.heading = heading
+    fn set_heading(&mut self, heading: Heading) -> Result<(), SensorError> {
+        if !self.sensors.compass {
+            return Err(SensorError::NoCompass);
+        }
+        self.battery.turn(self.heading, heading);
+        if heading != self.heading {
+            self.turns += 1;
+        }
+        self.heading = heading;
+        Ok(())
     }
 
     /// Get the robots current heading
-    fn get_heading(&self) -> Heading {
-        self.heading
+    fn get_heading(&self) -> Result<Heading, SensorError> {
+        if !self.sensors.compass {
+            return Err(SensorError::NoCompass);
+        }
+        Ok(self.heading)
     }
 
     /// Get the location of the robot
-    fn get_goal_location(&self) -> Point {
-        self.target
+    fn get_goal_location(&self) -> Result<Point, SensorError> {
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        Ok(self.target)
     }
     
     /// Get the current location of the robot
-    fn get_location(&self) -> Point {
-        self.location
+    fn get_location(&self) -> Result<Point, SensorError> {
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        Ok(self.location)
     }
     
     /// Get the current maze
-    fn get_maze(&self) -> &Maze<Self::Tiles> {
-        self.maze.borrow()
+    fn get_maze(&self) -> Result<MazeRef<'_, Self::Tiles>, SensorError> {
+        if !self.sensors.full_map {
+            return Err(SensorError::NoMap);
+        }
+        Ok(MazeRef::Borrowed(self.maze.borrow()))
+    }
+
+    /// Get the tiles around the robot
+    fn get_local_view(&self) -> Result<LocalView<Self::Tiles>, SensorError> {
+        let radius = self.sensors.view_radius.ok_or(SensorError::NoLocalView)?;
+        let view = LocalView::new(&self.maze, &self.state, self.location, self.heading, radius);
+        self.map.borrow_mut().record_view(&view, self.location, self.heading);
+        Ok(view)
+    }
+
+    /// Get the direction of the goal relative to the robot
+    fn get_goal_bearing(&self) -> Result<f64, SensorError> {
+        if !self.sensors.goal_bearing {
+            return Err(SensorError::NoGoalBearing);
+        }
+        Ok(sensors::bearing(self.location, self.target, self.heading))
+    }
+
+    fn at_goal(&self) -> bool {
+        self.location == self.target
     }
 
     /// Sleep for a bit. Time is a millisecond value.
@@ -87,58 +201,86 @@ impl Robot for DefaultRobot {
 
     fn get_collisions(&self) -> i64 { self.collisions }
 
+    fn get_turns(&self) -> i64 { self.turns }
+
+    fn get_time(&self) -> u64 { self.time }
+
+    fn get_sensor_reads(&self) -> i64 { self.sensor_reads.get() }
+
+    fn get_health(&self) -> u32 { self.health }
+
+    fn get_energy(&self) -> Option<u64> { self.battery.remaining() }
+
+    fn get_energy_used(&self) -> u64 { self.battery.used() }
+
+    fn get_inventory(&self) -> Inventory { self.state.get_inventory() }
+
+    fn get_trail(&self) -> VisitTrail { self.trail.clone() }
+
+    fn get_map(&self) -> Result<RobotMap, SensorError> {
+        if !self.sensors.discovered_map {
+            return Err(SensorError::NoDiscoveredMap);
+        }
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        Ok(self.map.borrow().clone())
+    }
+
     /// Print the state of the maze and robot position.
     /// Bordered by *, recommended to wipe the screen before calling
     /// as by default uses the (0, 0) cursor position to start drawing.
+    /// With fog of war on, cells the robot hasn't sensed are drawn as `?`.
     fn print(&self) {
-        let h = self.get_maze().get_height();
-        let w = self.get_maze().get_width();
+        let h = self.maze.get_height();
+        let w = self.maze.get_width();
         print!("{esc}[H", esc = 27 as char);
         println!(
-            "Steps: {:?}\tCollisions: {:?}",
+            "Steps: {:?}\tCollisions: {:?}\tTime: {:?}\tHealth: {:?}",
             self.get_steps(),
-            self.get_collisions()
+            self.get_collisions(),
+            self.get_time(),
+            self.get_health()
         );
 
         for _ in 0..w + 2 {
             print!("*");
         }
 
-        print!("\n");
+        println!();
         for i in 0..h {
             print!("*");
             for j in 0..w {
                 let pos = Point(j, i);
 
-                if self.get_location().eq(&pos) {
-                    match self.get_heading() {
+                if self.location.eq(&pos) {
+                    match self.heading {
                         Heading::North => print!("^"),
                         Heading::East => print!(">"),
                         Heading::South => print!("v"),
                         Heading::West => print!("<"),
                     }
-                } else if self.get_goal_location().eq(&pos) {
-                    match self.get_maze().get_cell(Point(j, i)) {
-                        Some(Tile::Passage) => print!("G"),
+                } else if self.target.eq(&pos) {
+                    match self.maze.get_cell(Point(j, i)) {
+                        Some(tile) if tile.can_walk() => print!("G"),
                         _ => print!("X")
                     }
-                } else {
-                    match self.get_maze().get_cell(Point(j, i)) {
-                        None => (),
-                        Some(Tile::Wall) => print!("█"),
-                        Some(Tile::Passage) => print!(" "),
-                        Some(Tile::BeenBefore) => print!("░"),
-                    }
+                } else if self.fog_of_war && !self.map.borrow().is_known(pos) {
+                    print!("?");
+                } else if self.trail.has_visited(pos) {
+                    print!("░");
+                } else if let Some(tile) = self.maze.get_cell(Point(j, i)) {
+                    print!("{}", self.state.effective_tile(*tile).symbol());
                 }
             }
-            print!("*\n");
+            println!("*");
         }
 
         for _ in 0..w + 2 {
             print!("*");
         }
 
-        print!("\n");
+        println!();
     }
 }
 
@@ -148,6 +290,13 @@ impl private::Robot for DefaultRobot {
         self.maze = maze;
         self.set_target_location(self.maze.get_finish());
         self.set_location(self.maze.get_start());
+        self.trail.resize(self.maze.get_width(), self.maze.get_height());
+        self.trail.visit(self.location);
+        self.map.get_mut().resize(self.maze.get_width(), self.maze.get_height());
+        self.map.get_mut().mark(self.location, MapCell::Open);
+        self.shortest_path = ShortestPathSolver::new().solve(&self.maze).map(|solution| solution.get_steps());
+        self.runs = 0;
+        self.reset_run_state();
     }
 
     /// Set the current location of the robot
@@ -155,39 +304,172 @@ impl private::Robot for DefaultRobot {
         self.location = loc
     }
 
-    /// Reset everything
-    fn reset(&mut self) {}
+    /// Put the robot back at the start for another run.
+    /// Anything it has mapped is remembered.
+    fn reset(&mut self) {
+        self.set_location(self.maze.get_start());
+        self.trail.clear();
+        self.trail.visit(self.location);
+        self.runs += 1;
+        self.reset_run_state();
+    }
 
     /// Set where the robots target is
     fn set_target_location(&mut self, loc: Point) {
         self.target = loc
     }
 
+    fn set_range_limit(&mut self, limit: Option<usize>) {
+        self.range_limit = limit
+    }
+
+    fn set_noise(&mut self, noise: Option<NoiseModel>) {
+        self.noise = noise.map(Noise::new)
+    }
+
+    fn set_energy(&mut self, energy: Option<EnergyModel>) {
+        self.battery = Battery::new(energy)
+    }
+
+    fn set_sensors(&mut self, sensors: SensorConfig) {
+        self.sensors = sensors
+    }
+
+    fn set_fog_of_war(&mut self, fog: bool) {
+        self.fog_of_war = fog
+    }
+
+    fn shortest_path(&self) -> Option<usize> {
+        self.shortest_path
+    }
+
+    fn current_location(&self) -> Point {
+        self.location
+    }
+
+    fn current_heading(&self) -> Heading {
+        self.heading
+    }
+
     /// Advance the robot on. Define at crate level privacy
     /// to stop an external robot controller calling this at the wrong time
     /// Should be implemented by the concrete Robot impl.
-    // BUG: This will panic if robot tries to exit off the top or left side of the maze. 
     fn advance(&mut self) {
-        if self.active {
-            let Point(locx, locy) = self.get_location();
-            // println!("{:?} {:?}", locx, locy);
-            let new_loc = match self.get_heading() {
-                Heading::North => Point(locx, locy - 1),
-                Heading::East => Point(locx + 1, locy),
-                Heading::South => Point(locx, locy + 1),
-                Heading::West => Point(locx - 1, locy)
+        if self.active && !self.is_destroyed() && !self.is_out_of_energy() {
+            let heading = match &self.noise {
+                Some(noise) => noise.heading(self.heading),
+                None => Some(self.heading),
+            };
+            let Some(heading) = heading else {
+                // Stalled, the wheels spun without going anywhere
+                self.time += 1;
+                self.battery.movement(1);
+                return;
             };
-            // println!("heading {:?}", self.get_heading());
-            // println!("new pos {:?}\t old pos {:?}", new_loc, Point(locx, locy));
-
-            if self.maze.can_move(new_loc) {
-                self.steps += 1;
-                self.set_location(new_loc);
-            } else {
-                self.collisions += 1
-            }
 
-            self.maze.set_cell(Point(locx, locy), Tile::BeenBefore);
+            match rules::resolve_move(&self.maze, &mut self.state, self.location, heading) {
+                Move::Moved { to, cost, damage } => {
+                    self.steps += 1;
+                    self.time += cost as u64;
+                    self.battery.movement(cost as u64);
+                    self.health = self.health.saturating_sub(damage);
+                    self.set_location(to);
+                    self.trail.visit(to);
+                    self.map.get_mut().mark(to, MapCell::Open);
+                }
+                Move::Destroyed { to, cost } => {
+                    self.steps += 1;
+                    self.time += cost as u64;
+                    self.battery.movement(cost as u64);
+                    self.health = 0;
+                    self.set_location(to);
+                    self.trail.visit(to);
+                    self.map.get_mut().mark(to, MapCell::Open);
+                }
+                Move::Blocked => {
+                    if let Some(wall) = heading.next_point(self.location) {
+                        self.map.get_mut().mark(wall, MapCell::Wall);
+                    }
+                    self.collisions += 1;
+                    self.time += 1;
+                    self.battery.collision();
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::{blank_generator::BlankGenerator, Generator};
+    use private::Robot as _;
+
+    #[test]
+    fn advance_records_trail_without_changing_maze() {
+        let maze = BlankGenerator::new().generate_maze();
+        let mut robot = DefaultRobot::default();
+        robot.set_maze(Box::new(maze.clone()));
+        robot.set_heading(Heading::East).unwrap();
+
+        robot.advance();
+        robot.face(Facing::Behind);
+        robot.advance();
+
+        assert_eq!(*robot.get_maze().unwrap(), maze);
+        assert_eq!(robot.look(Facing::Behind), Tile::Passage);
+
+        let trail = robot.get_trail();
+        assert_eq!(trail.get_visits(maze.get_start()), 2);
+        assert_eq!(trail.unique_cells(), 2);
+        assert_eq!(trail.revisits(), 1);
+    }
+
+    #[test]
+    fn range_counts_open_cells() {
+        let mut robot = DefaultRobot::default();
+        robot.set_maze(Box::new(BlankGenerator::new().generate_maze()));
+        robot.set_heading(Heading::East).unwrap();
+
+        assert_eq!(robot.range(Facing::Ahead), 17);
+        assert_eq!(robot.range(Facing::Left), 0);
+        let map = robot.get_map().unwrap();
+        assert_eq!(map.get(Point(18, 1)), MapCell::Open);
+        assert_eq!(map.get(Point(19, 1)), MapCell::Wall);
+        assert_eq!(map.get(Point(1, 0)), MapCell::Wall);
+        assert!(!map.is_known(Point(1, 2)));
+        robot.set_range_limit(Some(5));
+        assert_eq!(robot.range(Facing::Ahead), 5);
+        assert_eq!(robot.range(Facing::Right), 5);
+        assert_eq!(robot.get_sensor_reads(), 4);
+    }
+
+    #[test]
+    fn sensors_can_be_restricted() {
+        let mut robot = DefaultRobot::default();
+        robot.set_maze(Box::new(BlankGenerator::new().generate_maze()));
+        robot.set_heading(Heading::East).unwrap();
+
+        let view = robot.get_local_view().unwrap();
+        assert_eq!(view.get_radius(), 1);
+        assert_eq!(view.get(0, -1), Some(&Tile::Wall));
+        assert_eq!(view.get(1, 0), Some(&Tile::Passage));
+        assert_eq!(view.get(2, 0), None);
+        assert!(robot.get_goal_bearing().unwrap() > 0.0);
+
+        robot.set_sensors(SensorConfig::blind());
+        assert!(matches!(robot.get_maze(), Err(SensorError::NoMap)));
+        assert_eq!(robot.get_location(), Err(SensorError::NoPosition));
+        assert_eq!(robot.get_local_view(), Err(SensorError::NoLocalView));
+        assert_eq!(robot.set_heading(Heading::North), Err(SensorError::NoCompass));
+        assert_eq!(robot.get_map(), Err(SensorError::NoDiscoveredMap));
+        assert_eq!(robot.look(Facing::Ahead), Tile::Passage);
+
+        // The map would give away where the robot is, so needs the position sensor too
+        robot.set_sensors(SensorConfig { discovered_map: true, ..SensorConfig::blind() });
+        assert_eq!(robot.get_map(), Err(SensorError::NoPosition));
+        robot.set_sensors(SensorConfig { discovered_map: true, position: true, ..SensorConfig::blind() });
+        assert!(robot.get_map().unwrap().is_known(Point(2, 1)));
+        assert_eq!(robot.get_local_view(), Err(SensorError::NoLocalView));
+    }
+}
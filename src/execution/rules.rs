@@ -0,0 +1,177 @@
+use crate::{generation::Maze, Point};
+
+use super::{Heading, KeyColour, SwitchGroup, Tile, TileType};
+
+/// The keys a robot is carrying
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Inventory {
+    keys: u8,
+}
+
+impl Inventory {
+    /// Is the robot carrying a key of this colour
+    pub fn has_key(&self, colour: KeyColour) -> bool {
+        self.keys & (1 << colour as u8) != 0
+    }
+
+    /// Every colour of key the robot is carrying
+    pub fn get_keys(&self) -> Vec<KeyColour> {
+        KeyColour::ALL.into_iter().filter(|colour| self.has_key(*colour)).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys == 0
+    }
+
+    pub(crate) fn add_key(&mut self, colour: KeyColour) {
+        self.keys |= 1 << colour as u8
+    }
+}
+
+/// The parts of a maze which change as the robot moves through it during a run.
+/// The maze itself is never changed, tiles are seen through this state instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RunState {
+    inventory: Inventory,
+    /// Bit set for each switch group which has been toggled an odd number of times
+    switched: u64,
+}
+
+impl RunState {
+    pub fn get_inventory(&self) -> Inventory {
+        self.inventory
+    }
+
+    /// Has this group of switch walls been toggled from how the maze started
+    pub fn is_switched(&self, group: SwitchGroup) -> bool {
+        self.switched & (1 << group.get()) != 0
+    }
+
+    /// The tile as it currently appears, with switch walls opened or closed
+    pub fn effective_tile(&self, tile: Tile) -> Tile {
+        match tile {
+            Tile::SwitchWall { group, open } => Tile::SwitchWall {
+                group,
+                open: open != self.is_switched(group),
+            },
+            tile => tile,
+        }
+    }
+
+    /// Can the robot move onto this tile right now, travelling in `heading`
+    pub fn can_enter(&self, tile: Tile, heading: Heading) -> bool {
+        match self.effective_tile(tile) {
+            Tile::Door(colour) => self.inventory.has_key(colour),
+            Tile::OneWay(allowed) => allowed == heading,
+            tile => tile.can_walk(),
+        }
+    }
+
+    /// Apply the effects of the robot moving onto this tile
+    pub(crate) fn enter(&mut self, tile: Tile) {
+        match tile {
+            Tile::Key(colour) => self.inventory.add_key(colour),
+            Tile::Switch(group) => self.switched ^= 1 << group.get(),
+            _ => (),
+        }
+    }
+
+    pub(crate) fn add_key(&mut self, colour: KeyColour) {
+        self.inventory.add_key(colour)
+    }
+}
+
+/// Result of a robot trying to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Move {
+    /// Moved onto a new tile, taking `cost` ticks and `damage` health
+    Moved { to: Point, cost: u32, damage: u32 },
+    /// Moved onto a pit at `to`, which is the end of the robot
+    Destroyed { to: Point, cost: u32 },
+    /// Hit a wall or something else which can't be passed
+    Blocked,
+}
+
+/// Work out where a robot at `from` ends up when moving towards `heading`,
+/// updating the run state with anything picked up on the way.
+/// Shared by the robots and the solvers so they agree on the rules.
+pub(crate) fn resolve_move(maze: &Maze<Tile>, state: &mut RunState, from: Point, heading: Heading) -> Move {
+    let (mut to, tile) = match step(maze, state, from, heading) {
+        Some(moved) => moved,
+        None => return Move::Blocked,
+    };
+    let mut cost = tile.cost();
+    let mut damage = damage_from(tile);
+    if tile == Tile::Pit {
+        return Move::Destroyed { to, cost };
+    }
+
+    // Slide along any ice, stopping without a collision when blocked
+    while maze.get_cell(to) == Some(&Tile::Ice) {
+        match step(maze, state, to, heading) {
+            Some((next, tile)) => {
+                to = next;
+                cost += tile.cost();
+                damage += damage_from(tile);
+                if tile == Tile::Pit {
+                    return Move::Destroyed { to, cost };
+                }
+            }
+            None => break,
+        }
+    }
+
+    Move::Moved { to, cost, damage }
+}
+
+/// Move a single tile, following any teleporter. Gives where the robot
+/// ends up and the tile it moved onto, or `None` if it can't move that way.
+fn step(maze: &Maze<Tile>, state: &mut RunState, from: Point, heading: Heading) -> Option<(Point, Tile)> {
+    let to = heading.next_point(from)?;
+    let tile = *maze.get_cell(to)?;
+    if !state.can_enter(tile, heading) {
+        return None;
+    }
+
+    state.enter(tile);
+    let to = match tile {
+        Tile::Teleporter(channel) => teleport_exit(maze, channel, to).unwrap_or(to),
+        _ => to,
+    };
+    Some((to, tile))
+}
+
+/// Health lost by moving onto the tile
+fn damage_from(tile: Tile) -> u32 {
+    match tile {
+        Tile::Damage(amount) => amount as u32,
+        _ => 0,
+    }
+}
+
+/// Number of cells which can be seen from `from` looking towards `heading`
+/// before a wall, the edge of the maze, or the limit is reached
+pub(crate) fn range(maze: &Maze<Tile>, state: &RunState, from: Point, heading: Heading, limit: Option<usize>) -> usize {
+    let mut count = 0;
+    let mut point = from;
+    while limit.is_none_or(|limit| count < limit) {
+        match heading.next_point(point).and_then(|next| Some((next, maze.get_cell(next)?))) {
+            Some((next, tile)) if state.effective_tile(*tile).can_walk() => {
+                count += 1;
+                point = next;
+            }
+            _ => break,
+        }
+    }
+    count
+}
+
+/// The other teleporter on this channel, if the maze has one
+fn teleport_exit(maze: &Maze<Tile>, channel: u8, entrance: Point) -> Option<Point> {
+    maze.get_grid().iter().enumerate().find_map(|(y, row)| {
+        row.iter().enumerate().find_map(|(x, tile)| {
+            let point = Point(x, y);
+            (*tile == Tile::Teleporter(channel) && point != entrance).then_some(point)
+        })
+    })
+}
@@ -0,0 +1,181 @@
+use std::{error::Error, fmt, ops::Deref, sync::RwLockReadGuard};
+
+use crate::{generation::Maze, Point};
+
+use super::{rules::RunState, Facing, Heading, Tile, TileType};
+
+/// Controls which of the robot's sensors a controller is allowed to use.
+/// Anything not allowed returns a `SensorError` when called.
+/// The default allows everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorConfig {
+    /// `get_maze` gives the whole map
+    pub full_map: bool,
+    /// `get_local_view` gives the tiles within this many cells of the robot
+    pub view_radius: Option<usize>,
+    /// `get_map` gives the map the robot has built from what it has sensed.
+    /// The map is in maze coordinates and shows where the robot has been,
+    /// so it also needs `position`.
+    pub discovered_map: bool,
+    /// `get_location` and `get_goal_location` give absolute positions
+    pub position: bool,
+    /// `get_goal_bearing` gives the direction of the goal
+    pub goal_bearing: bool,
+    /// `get_heading` and `set_heading` work with absolute headings
+    pub compass: bool,
+}
+
+impl Default for SensorConfig {
+    fn default() -> Self {
+        SensorConfig {
+            full_map: true,
+            view_radius: Some(1),
+            discovered_map: true,
+            position: true,
+            goal_bearing: true,
+            compass: true,
+        }
+    }
+}
+
+impl SensorConfig {
+    /// Nothing but `look`, `range` and turning relative to the robot
+    pub fn blind() -> Self {
+        SensorConfig {
+            full_map: false,
+            view_radius: None,
+            discovered_map: false,
+            position: false,
+            goal_bearing: false,
+            compass: false,
+        }
+    }
+}
+
+/// A sensor was used which the controller isn't allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorError {
+    NoMap,
+    NoLocalView,
+    NoDiscoveredMap,
+    NoPosition,
+    NoGoalBearing,
+    NoCompass,
+}
+
+impl fmt::Display for SensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sensor = match self {
+            SensorError::NoMap => "full map",
+            SensorError::NoLocalView => "local view",
+            SensorError::NoDiscoveredMap => "discovered map",
+            SensorError::NoPosition => "absolute position",
+            SensorError::NoGoalBearing => "goal bearing",
+            SensorError::NoCompass => "compass",
+        };
+        write!(f, "the {} sensor is not available to this controller", sensor)
+    }
+}
+
+impl Error for SensorError {}
+
+/// Read access to the maze a robot is in. Robots which share their maze
+/// with other threads hold a read lock on it for as long as this is kept,
+/// so don't hold onto it between calls to `control_robot`.
+pub enum MazeRef<'a, T: TileType + Default> {
+    Borrowed(&'a Maze<T>),
+    Locked(RwLockReadGuard<'a, Maze<T>>),
+}
+
+impl<T: TileType + Default> Deref for MazeRef<'_, T> {
+    type Target = Maze<T>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            MazeRef::Borrowed(maze) => maze,
+            MazeRef::Locked(guard) => guard,
+        }
+    }
+}
+
+impl<T: TileType + Default + fmt::Debug> fmt::Debug for MazeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+/// The tiles around the robot, relative to the way it is facing.
+/// Tiles off the edge of the maze are seen as walls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalView<T> {
+    radius: usize,
+    /// Rows from furthest ahead to furthest behind, each from left to right
+    tiles: Vec<Vec<T>>,
+}
+
+impl LocalView<Tile> {
+    pub(crate) fn new(maze: &Maze<Tile>, state: &RunState, location: Point, heading: Heading, radius: usize) -> Self {
+        let r = radius as isize;
+        let ahead = heading;
+        let right = heading.augment_heading(Facing::Right);
+
+        let tiles = (-r..=r)
+            .rev()
+            .map(|forward| {
+                (-r..=r)
+                    .map(|side| {
+                        offset(location, ahead, forward)
+                            .and_then(|point| offset(point, right, side))
+                            .and_then(|point| maze.get_cell(point))
+                            .map_or(Tile::Wall, |tile| state.effective_tile(*tile))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        LocalView { radius, tiles }
+    }
+}
+
+impl<T> LocalView<T> {
+    pub fn get_radius(&self) -> usize {
+        self.radius
+    }
+
+    /// The tile `ahead` cells in front of and `right` cells to the right of the
+    /// robot. Negative values are behind and to the left.
+    pub fn get(&self, ahead: isize, right: isize) -> Option<&T> {
+        let r = self.radius as isize;
+        if ahead.abs() > r || right.abs() > r {
+            return None;
+        }
+        Some(&self.tiles[(r - ahead) as usize][(right + r) as usize])
+    }
+}
+
+/// Move `distance` cells towards `heading`, negative going the other way
+pub(crate) fn offset(point: Point, heading: Heading, distance: isize) -> Option<Point> {
+    let heading = if distance < 0 { heading.augment_heading(Facing::Behind) } else { heading };
+    (0..distance.unsigned_abs()).try_fold(point, |point, _| heading.next_point(point))
+}
+
+/// Angle in degrees to turn clockwise from `heading` to face `to` from `from`,
+/// between -180 and 180
+pub(crate) fn bearing(from: Point, to: Point, heading: Heading) -> f64 {
+    let dx = to.get_x() as f64 - from.get_x() as f64;
+    let dy = to.get_y() as f64 - from.get_y() as f64;
+    if dx == 0.0 && dy == 0.0 {
+        return 0.0;
+    }
+
+    let absolute = dx.atan2(-dy).to_degrees();
+    let facing = match heading {
+        Heading::North => 0.0,
+        Heading::East => 90.0,
+        Heading::South => 180.0,
+        Heading::West => 270.0,
+    };
+
+    let relative = (absolute - facing).rem_euclid(360.0);
+    if relative > 180.0 { relative - 360.0 } else { relative }
+}
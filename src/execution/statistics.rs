@@ -0,0 +1,193 @@
+use std::fmt;
+
+use super::RobotStatistics;
+
+/// Mean, median and best of one measurement across several runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aggregate {
+    pub mean: f64,
+    pub median: f64,
+    pub best: f64,
+}
+
+impl Aggregate {
+    /// Aggregate the values, `None` if there aren't any.
+    /// `higher_is_better` picks whether the best is the largest or smallest value.
+    pub fn of(values: impl IntoIterator<Item = f64>, higher_is_better: bool) -> Option<Self> {
+        let mut values: Vec<f64> = values.into_iter().collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+
+        let len = values.len();
+        let median = if len.is_multiple_of(2) {
+            (values[len / 2 - 1] + values[len / 2]) / 2.0
+        } else {
+            values[len / 2]
+        };
+
+        Some(Aggregate {
+            mean: values.iter().sum::<f64>() / len as f64,
+            median,
+            best: if higher_is_better { values[len - 1] } else { values[0] },
+        })
+    }
+}
+
+/// Summary of a set of runs. Everything but the efficiency covers every run,
+/// the efficiency only covers runs which reached the goal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    runs: usize,
+    goals_reached: usize,
+    steps: Option<Aggregate>,
+    collisions: Option<Aggregate>,
+    turns: Option<Aggregate>,
+    unique_cells: Option<Aggregate>,
+    revisits: Option<Aggregate>,
+    time: Option<Aggregate>,
+    wall_time: Option<Aggregate>,
+    control_time: Option<Aggregate>,
+    efficiency: Option<Aggregate>,
+}
+
+impl RunSummary {
+    pub fn from_runs(runs: &[RobotStatistics]) -> Self {
+        let lowest = |value: fn(&RobotStatistics) -> f64| Aggregate::of(runs.iter().map(value), false);
+
+        RunSummary {
+            runs: runs.len(),
+            goals_reached: runs.iter().filter(|run| run.goal_reached()).count(),
+            steps: lowest(|run| run.get_steps() as f64),
+            collisions: lowest(|run| run.get_collisions() as f64),
+            turns: lowest(|run| run.get_turns() as f64),
+            unique_cells: lowest(|run| run.get_unique_cells() as f64),
+            revisits: lowest(|run| run.get_revisits() as f64),
+            time: lowest(|run| run.get_time() as f64),
+            wall_time: lowest(|run| run.get_wall_time().as_secs_f64()),
+            control_time: lowest(|run| run.get_control_time().as_secs_f64()),
+            efficiency: Aggregate::of(runs.iter().filter_map(|run| run.get_efficiency()), true),
+        }
+    }
+
+    pub fn get_runs(&self) -> usize {
+        self.runs
+    }
+
+    pub fn get_goals_reached(&self) -> usize {
+        self.goals_reached
+    }
+
+    pub fn get_steps(&self) -> Option<Aggregate> {
+        self.steps
+    }
+
+    pub fn get_collisions(&self) -> Option<Aggregate> {
+        self.collisions
+    }
+
+    pub fn get_turns(&self) -> Option<Aggregate> {
+        self.turns
+    }
+
+    pub fn get_unique_cells(&self) -> Option<Aggregate> {
+        self.unique_cells
+    }
+
+    pub fn get_revisits(&self) -> Option<Aggregate> {
+        self.revisits
+    }
+
+    /// Ticks spent moving
+    pub fn get_time(&self) -> Option<Aggregate> {
+        self.time
+    }
+
+    /// Real time in seconds
+    pub fn get_wall_time(&self) -> Option<Aggregate> {
+        self.wall_time
+    }
+
+    /// Real time in seconds spent inside the controller
+    pub fn get_control_time(&self) -> Option<Aggregate> {
+        self.control_time
+    }
+
+    pub fn get_efficiency(&self) -> Option<Aggregate> {
+        self.efficiency
+    }
+}
+
+/// Steps taken on each of a set of runs, to see how a controller which
+/// learns improves from run to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LearningCurve {
+    steps: Vec<i64>,
+    goals_reached: Vec<bool>,
+}
+
+impl LearningCurve {
+    pub fn from_runs(runs: &[RobotStatistics]) -> Self {
+        LearningCurve {
+            steps: runs.iter().map(|run| run.get_steps()).collect(),
+            goals_reached: runs.iter().map(|run| run.goal_reached()).collect(),
+        }
+    }
+
+    pub fn get_steps(&self) -> &[i64] {
+        &self.steps
+    }
+
+    /// Mean steps over each run and up to `window - 1` runs before it,
+    /// smoothing out runs which explored more than others
+    pub fn moving_average(&self, window: usize) -> Vec<f64> {
+        let window = window.max(1);
+        (0..self.steps.len())
+            .map(|run| {
+                let runs = &self.steps[(run + 1).saturating_sub(window)..=run];
+                runs.iter().sum::<i64>() as f64 / runs.len() as f64
+            })
+            .collect()
+    }
+}
+
+/// One line per run with a bar for the steps taken, marking runs which
+/// didn't reach the goal
+impl fmt::Display for LearningCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let most = self.steps.iter().copied().max().unwrap_or(0).max(1);
+        for (run, (steps, reached)) in self.steps.iter().zip(&self.goals_reached).enumerate() {
+            let bar = "#".repeat((steps * 40 / most) as usize);
+            writeln!(f, "{:>4} {:>6} {} {}", run, steps, if *reached { ' ' } else { 'x' }, bar)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::RunOutcome;
+
+    #[test]
+    fn summarises_runs() {
+        let runs: Vec<RobotStatistics> = [(10, true), (4, true), (7, false)]
+            .into_iter()
+            .map(|(steps, reached)| {
+                let mut run = RobotStatistics::default();
+                run.set_steps(steps);
+                run.set_shortest_path(Some(4));
+                run.set_outcome(if reached { RunOutcome::ReachedGoal } else { RunOutcome::OutOfSteps });
+                run
+            })
+            .collect();
+
+        let summary = RunSummary::from_runs(&runs);
+        assert_eq!(summary.get_runs(), 3);
+        assert_eq!(summary.get_goals_reached(), 2);
+        assert_eq!(summary.get_steps(), Some(Aggregate { mean: 7.0, median: 7.0, best: 4.0 }));
+        assert_eq!(summary.get_efficiency(), Some(Aggregate { mean: 0.7, median: 0.7, best: 1.0 }));
+        assert_eq!(RunSummary::from_runs(&[]).get_steps(), None);
+    }
+}
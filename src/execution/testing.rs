@@ -0,0 +1,193 @@
+//! Checks a robot behaves like the robots in this crate, by running it
+//! through small scripted mazes. Turn on the `testing` feature to use it
+//! and call [`check_robot`] from a test:
+//! ```ignore
+//! #[test]
+//! fn my_robot_conforms() {
+//!     maze::execution::testing::check_robot::<MyRobot>().unwrap();
+//! }
+//! ```
+
+use std::{error::Error, fmt};
+
+use crate::{generation::Maze, Point};
+
+use super::{private, robot::DefaultRobot, threaded_robot::ThreadedRobot, Facing, Heading, Robot, Tile};
+
+/// A robot the checks can set up and move. Implemented for the robots in
+/// this crate, implement it for your own robot to check it.
+pub trait TestRobot: Robot<Tiles = Tile> + Sized {
+    /// Make a robot at the start of `maze`, with every sensor allowed
+    fn place(maze: Maze<Tile>) -> Self;
+
+    /// Move the robot on a tick the way its controller wrapper would
+    fn advance(&mut self);
+}
+
+impl TestRobot for DefaultRobot {
+    fn place(maze: Maze<Tile>) -> Self {
+        let mut robot = DefaultRobot::default();
+        private::Robot::set_maze(&mut robot, Box::new(maze));
+        robot
+    }
+
+    fn advance(&mut self) {
+        private::Robot::advance(self)
+    }
+}
+
+impl TestRobot for ThreadedRobot {
+    fn place(maze: Maze<Tile>) -> Self {
+        let mut robot = ThreadedRobot::default();
+        private::Robot::set_maze(&mut robot, Box::new(maze));
+        robot
+    }
+
+    fn advance(&mut self) {
+        private::Robot::advance(self)
+    }
+}
+
+/// A check the robot didn't pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckFailure {
+    /// Name of the check which failed
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for CheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} check failed: {}", self.check, self.message)
+    }
+}
+
+impl Error for CheckFailure {}
+
+fn expect<T: PartialEq + fmt::Debug>(check: &'static str, what: &str, actual: T, expected: T) -> Result<(), CheckFailure> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(CheckFailure {
+            check,
+            message: format!("expected {} to be {:?}, got {:?}", what, expected, actual),
+        })
+    }
+}
+
+/// Open maze of the given size running from `start` to `finish`
+fn room(width: usize, height: usize, start: Point, finish: Point) -> Maze<Tile> {
+    let mut maze = Maze::new(width, height);
+    maze.fill(Tile::Passage);
+    maze.set_start(start);
+    maze.set_finish(finish);
+    maze
+}
+
+/// Run every check, stopping at the first one which fails
+pub fn check_robot<R: TestRobot>() -> Result<(), CheckFailure> {
+    check_headings::<R>()?;
+    check_look::<R>()?;
+    check_collisions::<R>()?;
+    check_edges::<R>()?;
+    check_goal::<R>()
+}
+
+/// Turning relative to the robot and setting the heading both change the heading it reports
+pub fn check_headings<R: TestRobot>() -> Result<(), CheckFailure> {
+    const CHECK: &str = "headings";
+    let mut robot = R::place(room(3, 3, Point(1, 1), Point(2, 2)));
+
+    for face in [Facing::Right, Facing::Right, Facing::Behind, Facing::Left, Facing::Ahead] {
+        let expected = robot.get_heading().map(|heading| heading.augment_heading(face));
+        robot.face(face);
+        expect(CHECK, &format!("heading after facing {:?}", face), robot.get_heading(), expected)?;
+    }
+
+    for heading in Heading::ALL {
+        expect(CHECK, &format!("setting heading {:?}", heading), robot.set_heading(heading), Ok(()))?;
+        expect(CHECK, "heading", robot.get_heading(), Ok(heading))?;
+    }
+    Ok(())
+}
+
+/// Looking each way sees the right tile whichever way the robot is heading
+pub fn check_look<R: TestRobot>() -> Result<(), CheckFailure> {
+    const CHECK: &str = "look";
+    let centre = Point(1, 1);
+    let mut maze = room(3, 3, centre, Point(0, 0));
+    maze.set_cell(Point(1, 0), Tile::Wall);
+    maze.set_cell(Point(2, 1), Tile::Mud);
+    maze.set_cell(Point(1, 2), Tile::Sand);
+    maze.set_cell(Point(0, 1), Tile::Road);
+
+    let mut robot = R::place(maze.clone());
+    for heading in Heading::ALL {
+        robot.face(robot.get_heading().unwrap_or(heading).facing_towards(heading));
+        for face in [Facing::Ahead, Facing::Right, Facing::Behind, Facing::Left] {
+            let point = heading.augment_heading(face).next_point(centre);
+            let expected = point.and_then(|point| maze.get_cell(point)).copied();
+            expect(CHECK, &format!("looking {:?} while heading {:?}", face, heading), Some(robot.look(face)), expected)?;
+        }
+    }
+    Ok(())
+}
+
+/// Driving into a wall counts a collision every tick without moving
+pub fn check_collisions<R: TestRobot>() -> Result<(), CheckFailure> {
+    const CHECK: &str = "collisions";
+    let mut maze = room(4, 1, Point(0, 0), Point(3, 0));
+    maze.set_cell(Point(1, 0), Tile::Wall);
+
+    let mut robot = R::place(maze);
+    expect(CHECK, "setting heading", robot.set_heading(Heading::East), Ok(()))?;
+    for _ in 0..3 {
+        robot.advance();
+    }
+    expect(CHECK, "location", robot.get_location(), Ok(Point(0, 0)))?;
+    expect(CHECK, "collisions", robot.get_collisions(), 3)?;
+    expect(CHECK, "steps", robot.get_steps(), 0)
+}
+
+/// The edge of the maze looks and acts like a wall
+pub fn check_edges<R: TestRobot>() -> Result<(), CheckFailure> {
+    const CHECK: &str = "edges";
+    let mut robot = R::place(room(2, 2, Point(0, 0), Point(1, 1)));
+
+    for heading in [Heading::North, Heading::West] {
+        expect(CHECK, "setting heading", robot.set_heading(heading), Ok(()))?;
+        expect(CHECK, &format!("looking off the {:?} edge", heading), robot.look(Facing::Ahead), Tile::Wall)?;
+        expect(CHECK, &format!("range off the {:?} edge", heading), robot.range(Facing::Ahead), 0)?;
+        robot.advance();
+        expect(CHECK, "location", robot.get_location(), Ok(Point(0, 0)))?;
+    }
+    expect(CHECK, "collisions", robot.get_collisions(), 2)
+}
+
+/// Moving updates the location and steps, and the robot knows when it's at the goal
+pub fn check_goal<R: TestRobot>() -> Result<(), CheckFailure> {
+    const CHECK: &str = "goal";
+    let mut robot = R::place(room(3, 1, Point(0, 0), Point(2, 0)));
+    expect(CHECK, "goal location", robot.get_goal_location(), Ok(Point(2, 0)))?;
+    expect(CHECK, "setting heading", robot.set_heading(Heading::East), Ok(()))?;
+
+    robot.advance();
+    expect(CHECK, "location", robot.get_location(), Ok(Point(1, 0)))?;
+    expect(CHECK, "at goal before reaching it", robot.at_goal(), false)?;
+
+    robot.advance();
+    expect(CHECK, "location", robot.get_location(), Ok(Point(2, 0)))?;
+    expect(CHECK, "steps", robot.get_steps(), 2)?;
+    expect(CHECK, "at goal", robot.at_goal(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crate_robots_pass_checks() {
+        assert_eq!(check_robot::<DefaultRobot>(), Ok(()));
+        assert_eq!(check_robot::<ThreadedRobot>(), Ok(()));
+    }
+}
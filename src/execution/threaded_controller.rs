@@ -1,16 +1,21 @@
 use std::{
-    sync::{mpsc::Sender, Arc, Mutex, RwLock},
+    sync::{mpsc::{Receiver, RecvTimeoutError, Sender}, Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 
 use crate::{Point, execution::Maze};
 
-use super::{polled_controller::PolledController, private, Controller, Heading, Robot, threaded_robot::ThreadedRobot};
+use super::{energy::EnergyModel, limits::RunLimits, noise::NoiseModel, observer::{ExecutionObserver, RunInfo}, polled_controller::PolledController, sensors::SensorConfig, statistics::RunSummary, trace::{RunTrace, TraceTick}, private, tick_delay, Controller, Heading, Robot, RobotStatistics, RunOutcome, StepEvent, threaded_robot::ThreadedRobot};
 use super::Tile;
 /// ThreadedController implementation
-pub trait ThreadedController: Default {
+pub trait ThreadedController {
     fn control_robot(&mut self, robot: &mut ThreadedRobot);
 
-    fn reset(&self) {}
+    /// Called as each run ends, with how it ended
+    fn run_finished(&mut self, _outcome: RunOutcome) {}
+
+    /// Called at the end of each run when the robot is put back at the start for another
+    fn reset(&mut self) {}
 }
 
 /// Blanket implementation to allow PolledControllers to be used as ThreadedControllers with no issue
@@ -19,31 +24,83 @@ where
     T: PolledController<ThreadedRobot>,
 {
     fn control_robot(&mut self, robot: &mut ThreadedRobot) {
-        self.control_robot(robot);
+        PolledController::control_robot(self, robot);
     }
 
-    fn reset(&self) {
-        self.reset()
+    fn run_finished(&mut self, outcome: RunOutcome) {
+        PolledController::run_finished(self, outcome)
     }
+
+    fn reset(&mut self) {
+        PolledController::reset(self)
+    }
+}
+
+/// How often a paused controller checks whether it has been stopped
+const PAUSE_POLL: Duration = Duration::from_millis(20);
+
+/// Commands which can be sent to a running threaded controller
+#[derive(Debug, Clone)]
+pub enum ThreadedCommand {
+    /// Stop moving the robot until resumed
+    Pause,
+    /// Carry on after being paused
+    Resume,
+    /// Move the robot once while paused
+    Step,
+    /// End the runs early
+    Abort,
+    /// Change the delay between moves
+    SetDelay(i32),
+    /// Swap to a different maze, starting the runs again on it
+    SetMaze(Arc<RwLock<Maze<Tile>>>),
+}
+
+/// What a threaded controller is currently doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerState {
+    Running,
+    Paused,
+    Finished,
 }
 
 #[derive(Debug, Clone)]
 pub struct ThreadedRobotProgress {
     pub finished: bool,
+    pub state: ControllerState,
     pub robot_pos: Point,
     pub target_loc: Point,
-    pub robot_head: Heading
+    pub robot_head: Heading,
+    /// How the run ended, once `finished` is set
+    pub outcome: Option<RunOutcome>,
 }
 
-#[derive(Default)]
 pub struct ThreadedControllerWrapper<C: ThreadedController>
 {
     robot: ThreadedRobot,
+    /// Gate owned by whoever made the wrapper, only ever read here
     active: Arc<Mutex<bool>>,
     thread_delay: Arc<Mutex<i32>>,
     progress_sender: Arc<Mutex<Option<Sender<ThreadedRobotProgress>>>>,
     latest_robot_update: Arc<Mutex<Option<ThreadedRobotProgress>>>,
     controller: C,
+    statistics: RobotStatistics,
+    limits: RunLimits,
+    runs: usize,
+    history: Vec<RobotStatistics>,
+    traces: Vec<RunTrace>,
+    observers: Vec<Box<dyn ExecutionObserver<ThreadedRobot> + Send>>,
+    /// Trace and start time of the run in progress
+    current_run: Option<(RunTrace, Instant)>,
+    /// Is there a set of runs in progress
+    in_session: bool,
+    /// Is the current run still going, cleared to stop it early
+    running: bool,
+    /// Has the robot been run since it was put on the maze, so needs
+    /// putting back at the start before a new set of runs
+    robot_used: bool,
+    commands: Option<Receiver<ThreadedCommand>>,
+    paused: bool,
 }
 
 impl<C> Controller<ThreadedRobot, Tile> for ThreadedControllerWrapper<C>
@@ -64,11 +121,12 @@ where
 
     fn set_robot(&mut self, robot: ThreadedRobot) {
         self.robot = robot;
+        self.robot_used = false;
     }
 
     fn get_delay(&self) -> i32 {
         match self.thread_delay.lock() {
-            Ok(val) => val.clone(),
+            Ok(val) => *val,
             Err(_) => 0,
         }
     }
@@ -80,65 +138,302 @@ where
 
     fn set_maze(&mut self, maze: crate::generation::Maze<Tile>) {
         self.robot.set_maze(Arc::new(RwLock::from(maze)));
+        self.robot_used = false;
+        self.in_session = false;
+        self.current_run = None;
     }
 
-    fn start(&mut self) {
+    /// Runs until every run has finished, taking commands from the command
+    /// receiver between moves if one has been set
+    fn start(&mut self) -> RunOutcome {
+        self.in_session = false;
+        self.current_run = None;
+        self.paused = false;
+        self.begin_run();
 
-        while !Robot::get_location(&self.robot).eq(&self.robot.get_goal_location())
-            && match self.active.lock() {
-                Ok(val) => *val,
-                Err(_) => false,
-            }
-        {
-            self.controller.control_robot(&mut self.robot);
-
-            private::Robot::advance(&mut self.robot);
-
-            // (self.callback)(&mut self.robot);
-            // println!("loc {:?}\ttarget_loc {:?}\theading {:?}", self.robot.get_location(), self.robot.get_goal_location(), self.robot.get_heading());
-            self.send_robot_update(false);
-            
-            if match self.thread_delay.lock() {
-                Ok(val) => *val > 0,
-                Err(_) => false,
-            } {
-                self.robot.sleep(self.get_delay());
+        loop {
+            self.handle_commands();
+            if let StepEvent::Finished(outcome) = self.step() {
+                if !self.in_session {
+                    return outcome;
+                }
             }
         }
-
-        println!("Robot finished. ");
-        self.send_robot_update(true);
-
-        // todo!("Reset & Active check in end of start");
     }
 
     fn reset(&mut self) {
-        match self.active.lock() {
-            Ok(mut val) => *val = false,
-            Err(_) => (),
-        };
-
+        self.running = false;
         self.controller.reset();
     }
 }
 
-impl<C: ThreadedController> ThreadedControllerWrapper<C> {
+impl<C: ThreadedController + Default> Default for ThreadedControllerWrapper<C> {
+    /// A wrapper with its own active flag, set so it can run
+    fn default() -> Self {
+        Self::new(Arc::new(Mutex::new(true)), Default::default(), Default::default())
+    }
+}
+
+impl<C: ThreadedController + Default> ThreadedControllerWrapper<C> {
+    /// `active` is a gate owned by the caller. Runs only carry on while it is
+    /// true, so set it before starting. The wrapper reads it but never changes it.
     pub fn new(
         active: Arc<Mutex<bool>>,
         thread_delay: Arc<Mutex<i32>>,
         latest_robot_update: Arc<Mutex<Option<ThreadedRobotProgress>>>,
+    ) -> Self {
+        Self::with_controller(C::default(), active, thread_delay, latest_robot_update)
+    }
+}
+
+impl<C: ThreadedController> ThreadedControllerWrapper<C> {
+    /// Wrap a controller which has already been made, such as one without a default.
+    /// `active` is read the same way as for `new`.
+    pub fn with_controller(
+        controller: C,
+        active: Arc<Mutex<bool>>,
+        thread_delay: Arc<Mutex<i32>>,
+        latest_robot_update: Arc<Mutex<Option<ThreadedRobotProgress>>>,
     ) -> Self {
         Self {
+            robot: Default::default(),
             active,
             thread_delay,
+            progress_sender: Default::default(),
             latest_robot_update,
-            ..Default::default()
+            controller,
+            statistics: Default::default(),
+            limits: RunLimits::default(),
+            runs: 1,
+            history: Vec::new(),
+            traces: Vec::new(),
+            observers: Vec::new(),
+            current_run: None,
+            in_session: false,
+            running: false,
+            robot_used: false,
+            commands: None,
+            paused: false,
+        }
+    }
+
+    /// Limit how far the robot's range sensor can see, `None` for no limit
+    pub fn set_range_limit(&mut self, limit: Option<usize>) {
+        private::Robot::set_range_limit(&mut self.robot, limit);
+    }
+
+    /// Make the robot's sensors and movement unreliable, `None` for a perfect robot
+    pub fn set_noise(&mut self, noise: Option<NoiseModel>) {
+        private::Robot::set_noise(&mut self.robot, noise);
+    }
+
+    /// Give the robot a battery which its actions drain, `None` for unlimited energy.
+    /// The battery is recharged at the start of each run.
+    pub fn set_energy(&mut self, energy: Option<EnergyModel>) {
+        private::Robot::set_energy(&mut self.robot, energy);
+    }
+
+    /// Stop runs early once the robot has taken too many steps, hit too many
+    /// walls or taken too long
+    pub fn set_limits(&mut self, limits: RunLimits) {
+        self.limits = limits;
+    }
+
+    /// Choose which sensors the controller is allowed to use
+    pub fn set_sensors(&mut self, sensors: SensorConfig) {
+        private::Robot::set_sensors(&mut self.robot, sensors);
+    }
+
+    /// Only show what the robot has discovered through `ThreadedRobot::get_shown_tile`
+    pub fn set_fog_of_war(&mut self, fog: bool) {
+        private::Robot::set_fog_of_war(&mut self.robot, fog);
+    }
+
+    /// Number of runs to make each time the controller is started, at least one
+    pub fn set_runs(&mut self, runs: usize) {
+        self.runs = runs.max(1);
+    }
+
+    pub fn get_runs(&self) -> usize {
+        self.runs.max(1)
+    }
+
+    /// Statistics for the current (or last) run
+    pub fn get_statistics(&self) -> &RobotStatistics {
+        &self.statistics
+    }
+
+    /// Statistics for each run finished since the controller was last started
+    pub fn get_history(&self) -> &[RobotStatistics] {
+        &self.history
+    }
+
+    /// Watch the controller as it runs, alongside any observers already added
+    pub fn add_observer(&mut self, observer: Box<dyn ExecutionObserver<ThreadedRobot> + Send>) {
+        self.observers.push(observer);
+    }
+
+    /// Trace of each run finished since the controller was last started
+    pub fn get_traces(&self) -> &[RunTrace] {
+        &self.traces
+    }
+
+    /// Summary of every run finished since the controller was last started
+    pub fn get_summary(&self) -> RunSummary {
+        RunSummary::from_runs(&self.history)
+    }
+
+    /// Run a single control and move cycle, starting the next run first if
+    /// one isn't already going. Says what happened to the robot.
+    pub fn step(&mut self) -> StepEvent {
+        if self.current_run.is_none() {
+            self.begin_run();
+        }
+        if let Some(outcome) = self.finished() {
+            return self.finish_run(outcome);
+        }
+
+        let (reads, collisions, steps) = (self.robot.get_sensor_reads(), self.robot.get_collisions(), self.robot.get_steps());
+        let control_started = Instant::now();
+        self.controller.control_robot(&mut self.robot);
+        self.statistics.add_control_time(control_started.elapsed());
+
+        let time = self.robot.get_time();
+        private::Robot::advance(&mut self.robot);
+        self.statistics.record_robot(&self.robot);
+
+        let position = private::Robot::current_location(&self.robot);
+        let collided = self.robot.get_collisions() > collisions;
+        let tick = TraceTick {
+            heading: private::Robot::current_heading(&self.robot),
+            sensor_reads: self.robot.get_sensor_reads() - reads,
+            position,
+            collided,
+        };
+        if let Some((trace, _)) = &mut self.current_run {
+            trace.push(tick);
+        }
+
+        self.send_robot_update(false);
+
+        if match self.thread_delay.lock() {
+            Ok(val) => *val > 0,
+            Err(_) => false,
+        } {
+            // Slower tiles take proportionally longer to cross
+            self.robot.sleep(tick_delay(self.get_delay(), self.robot.get_time().saturating_sub(time)));
+        }
+
+        let event = if let Some(outcome) = self.finished() {
+            StepEvent::Finished(outcome)
+        } else if collided {
+            StepEvent::Collided
+        } else if self.robot.get_steps() > steps {
+            StepEvent::Moved(position)
+        } else {
+            StepEvent::Stalled
+        };
+
+        for observer in &mut self.observers {
+            observer.on_tick(&self.robot, &tick, &event);
+            if collided {
+                observer.on_collision(&self.robot);
+            }
+        }
+
+        match event {
+            StepEvent::Finished(outcome) => self.finish_run(outcome),
+            event => event,
+        }
+    }
+
+    /// Keep stepping until `until` returns true for a step, or every run has finished.
+    /// Gives the last step's event.
+    pub fn run_until(&mut self, mut until: impl FnMut(&StepEvent, &ThreadedRobot) -> bool) -> StepEvent {
+        loop {
+            let event = self.step();
+            if until(&event, &self.robot) || (matches!(event, StepEvent::Finished(_)) && !self.in_session) {
+                return event;
+            }
         }
     }
 
+    /// Get ready for the next run, starting a new set of runs if needed
+    fn begin_run(&mut self) {
+        if !self.in_session {
+            if self.robot_used {
+                private::Robot::reset(&mut self.robot);
+            }
+            self.in_session = true;
+            self.history.clear();
+            self.traces.clear();
+        } else {
+            private::Robot::reset(&mut self.robot);
+            self.controller.reset();
+            for observer in &mut self.observers {
+                observer.on_reset(&self.robot);
+            }
+        }
+
+        self.statistics = RobotStatistics::default();
+        self.statistics.record_robot(&self.robot);
+        self.statistics.set_shortest_path(private::Robot::shortest_path(&self.robot));
+
+        let info = RunInfo {
+            run: self.history.len(),
+            start: private::Robot::current_location(&self.robot),
+            heading: private::Robot::current_heading(&self.robot),
+        };
+        for observer in &mut self.observers {
+            observer.on_run_start(&self.robot, &info);
+        }
+
+        self.current_run = Some((RunTrace::new(info.start, info.heading), Instant::now()));
+        self.running = true;
+        self.robot_used = true;
+    }
+
+    /// How the current run has ended, if it has
+    fn finished(&self) -> Option<RunOutcome> {
+        let started = self.current_run.as_ref().map_or_else(Instant::now, |(_, started)| *started);
+        RunOutcome::from_robot(&self.robot)
+            .or_else(|| self.limits.check(&self.robot, started))
+            .or_else(|| (!self.running || !self.is_active()).then_some(RunOutcome::Aborted))
+    }
+
+    /// Record the end of the current run, telling any listener once every run is done
+    fn finish_run(&mut self, outcome: RunOutcome) -> StepEvent {
+        if let Some((mut trace, started)) = self.current_run.take() {
+            self.statistics.record_trail(&self.robot.get_trail());
+            self.statistics.set_wall_time(started.elapsed());
+            self.statistics.set_outcome(outcome);
+            self.history.push(self.statistics.clone());
+            trace.finish(outcome);
+            self.traces.push(trace);
+            self.controller.run_finished(outcome);
+
+            for observer in &mut self.observers {
+                if outcome == RunOutcome::ReachedGoal {
+                    observer.on_goal_reached(&self.robot);
+                }
+                observer.on_run_end(&self.robot, &self.statistics);
+            }
+        }
+
+        if outcome == RunOutcome::Aborted || self.history.len() >= self.get_runs() {
+            self.in_session = false;
+            self.send_robot_update(true);
+        }
+
+        StepEvent::Finished(outcome)
+    }
+
     pub fn set_maze_ref(&mut self, maze: Arc<RwLock<Maze<Tile>>>) {
         if !*self.active.lock().unwrap() {
             self.robot.set_maze(maze);
+            self.robot_used = false;
+            self.in_session = false;
+            self.current_run = None;
         } 
     }
 
@@ -146,28 +441,248 @@ impl<C: ThreadedController> ThreadedControllerWrapper<C> {
         self.progress_sender = Arc::from(Mutex::from(Some(tx)));
     }
 
+    /// Take commands from this receiver while the controller is running
+    pub fn set_command_receiver(&mut self, rx: Receiver<ThreadedCommand>) {
+        self.commands = Some(rx);
+    }
+
+    fn is_active(&self) -> bool {
+        match self.active.lock() {
+            Ok(val) => *val,
+            Err(_) => false,
+        }
+    }
+
+    /// Apply any commands which have been sent, waiting for more while paused.
+    /// If the sender goes away the controller carries on as if resumed, and if
+    /// it is stopped through the shared active flag it ends the run.
+    fn handle_commands(&mut self) {
+        let Some(commands) = self.commands.take() else {
+            return;
+        };
+
+        loop {
+            let command = if self.paused {
+                match commands.recv_timeout(PAUSE_POLL) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) if self.is_active() => continue,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.paused = false;
+                        break;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => None,
+                }
+            } else {
+                commands.try_recv().ok()
+            };
+            let Some(command) = command else {
+                if self.paused {
+                    self.paused = false;
+                    self.send_robot_update(false);
+                }
+                break;
+            };
+
+            match command {
+                ThreadedCommand::Pause if !self.paused => {
+                    self.paused = true;
+                    self.send_robot_update(false);
+                }
+                ThreadedCommand::Resume if self.paused => {
+                    self.paused = false;
+                    self.send_robot_update(false);
+                    break;
+                }
+                ThreadedCommand::Step if self.paused => break,
+                ThreadedCommand::Abort => {
+                    self.running = false;
+                    self.paused = false;
+                    break;
+                }
+                ThreadedCommand::SetDelay(delay) => self.set_delay(delay),
+                ThreadedCommand::SetMaze(maze) => {
+                    self.robot.set_maze(maze);
+                    self.robot_used = false;
+                    self.in_session = false;
+                    self.current_run = None;
+                }
+                _ => (),
+            }
+        }
+
+        self.commands = Some(commands);
+    }
+
     pub fn send_robot_update(&mut self, finished: bool) {
+        let state = if finished {
+            ControllerState::Finished
+        } else if self.paused {
+            ControllerState::Paused
+        } else {
+            ControllerState::Running
+        };
         let message = ThreadedRobotProgress {
             finished,
-            robot_head: self.robot.get_heading(),
-            robot_pos: self.robot.get_location(),
-            target_loc: self.robot.get_goal_location()
+            state,
+            robot_head: private::Robot::current_heading(&self.robot),
+            robot_pos: private::Robot::current_location(&self.robot),
+            target_loc: self.robot.get_maze_ref().read().unwrap().get_finish(),
+            outcome: if finished { self.statistics.get_outcome() } else { None },
         };
 
-        match self.progress_sender.lock() {
-            Ok(lock) => {
-                if let Some(sender) = &*lock {
-                    let send_res = sender.send(message.clone());
-                    match send_res {
-                        Ok(_) => match self.latest_robot_update.lock() {
-                            Ok(mut opt_val) => *opt_val = Some(message),
-                            Err(_) => (),
-                        },
-                        Err(err) => println!("{:?}", err),
+        if let Ok(lock) = self.progress_sender.lock() {
+            if let Some(sender) = &*lock {
+                // Nobody listening any more isn't a problem for the run
+                if sender.send(message.clone()).is_ok() {
+                    if let Ok(mut opt_val) = self.latest_robot_update.lock() {
+                        *opt_val = Some(message)
                     }
                 }
             }
-            _ => (),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+    use crate::execution::observer::{StatisticsCollector, TickCallback, TraceRecorder};
+
+    #[derive(Default)]
+    struct Forward;
+
+    impl<R: Robot> PolledController<R> for Forward {
+        fn control_robot(&mut self, robot: &mut R) {
+            robot.set_heading(Heading::East).unwrap();
+        }
+    }
+
+    fn wrapper() -> ThreadedControllerWrapper<Forward> {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+
+        let mut wrapper = ThreadedControllerWrapper::<Forward>::default();
+        wrapper.set_maze(maze);
+        wrapper
+    }
+
+    #[test]
+    fn commands_pause_and_step_robot() {
+        let mut wrapper = wrapper();
+        let (command_tx, command_rx) = channel();
+        let (progress_tx, progress_rx) = channel();
+        wrapper.set_command_receiver(command_rx);
+        wrapper.set_sender(progress_tx);
+
+        for command in [ThreadedCommand::Pause, ThreadedCommand::Step, ThreadedCommand::SetDelay(0), ThreadedCommand::Resume] {
+            command_tx.send(command).unwrap();
+        }
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+
+        let states: Vec<_> = progress_rx.try_iter().map(|progress| (progress.state, progress.robot_pos)).collect();
+        assert_eq!(
+            states,
+            vec![
+                (ControllerState::Paused, Point(0, 0)),
+                (ControllerState::Paused, Point(1, 0)),
+                (ControllerState::Running, Point(1, 0)),
+                (ControllerState::Running, Point(2, 0)),
+                (ControllerState::Running, Point(3, 0)),
+                (ControllerState::Finished, Point(3, 0)),
+            ]
+        );
+        assert!(*wrapper.active.lock().unwrap());
+    }
+
+    #[test]
+    fn abort_command_ends_runs() {
+        let mut wrapper = wrapper();
+        let (command_tx, command_rx) = channel();
+        wrapper.set_command_receiver(command_rx);
+        wrapper.set_runs(3);
+
+        command_tx.send(ThreadedCommand::Abort).unwrap();
+        assert_eq!(wrapper.start(), RunOutcome::Aborted);
+        assert_eq!(wrapper.get_history().len(), 1);
+    }
+
+    #[test]
+    fn observers_see_every_run() {
+        let ticks = Arc::new(Mutex::new(0));
+        let statistics = StatisticsCollector::default();
+        let traces = TraceRecorder::default();
+
+        let mut wrapper = wrapper();
+        let counter = ticks.clone();
+        wrapper.add_observer(Box::new(TickCallback::new(move |_: &ThreadedRobot| *counter.lock().unwrap() += 1)));
+        wrapper.add_observer(Box::new(statistics.clone()));
+        wrapper.add_observer(Box::new(traces.clone()));
+        wrapper.set_runs(2);
+        wrapper.start();
+
+        assert_eq!(*ticks.lock().unwrap(), 6);
+        assert_eq!(statistics.get_runs().len(), 2);
+        assert_eq!(statistics.get_runs()[1].get_steps(), wrapper.get_history()[1].get_steps());
+        assert_eq!(traces.get_traces(), wrapper.get_traces());
+    }
+
+    #[test]
+    fn fog_of_war_hides_unsensed_tiles() {
+        let mut wrapper = wrapper();
+        assert_eq!(wrapper.get_robot().get_shown_tile(Point(3, 0)), Some(Tile::Passage));
+
+        wrapper.set_fog_of_war(true);
+        assert!(wrapper.get_robot().get_fog_of_war());
+        assert_eq!(wrapper.get_robot().get_shown_tile(Point(3, 0)), None);
+        assert_eq!(wrapper.get_robot().get_shown_tile(Point(0, 0)), Some(Tile::Passage));
+
+        wrapper.start();
+        assert_eq!(wrapper.get_robot().get_shown_tile(Point(3, 0)), Some(Tile::Passage));
+    }
+
+    #[test]
+    fn each_start_makes_a_new_run() {
+        let mut wrapper = wrapper();
+        for _ in 0..2 {
+            assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+            assert_eq!(wrapper.get_statistics().get_steps(), 3);
+        }
+        assert_eq!(wrapper.step(), StepEvent::Moved(Point(1, 0)));
+    }
+
+    #[test]
+    fn active_flag_is_only_read() {
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_finish(Point(3, 0));
+        let active = Arc::new(Mutex::new(false));
+        let mut wrapper = ThreadedControllerWrapper::<Forward>::new(active.clone(), Default::default(), Default::default());
+        wrapper.set_maze(maze);
+        assert_eq!(wrapper.start(), RunOutcome::Aborted);
+        assert_eq!(wrapper.get_statistics().get_steps(), 0);
+        assert!(!*active.lock().unwrap());
+
+        *active.lock().unwrap() = true;
+        wrapper.reset();
+        assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+        assert!(*active.lock().unwrap());
+    }
+
+    #[test]
+    fn clearing_active_stops_paused_run() {
+        let mut wrapper = wrapper();
+        let (command_tx, command_rx) = channel();
+        wrapper.set_command_receiver(command_rx);
+        command_tx.send(ThreadedCommand::Pause).unwrap();
+
+        let active = wrapper.active.clone();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            *active.lock().unwrap() = false;
+        });
+        assert_eq!(wrapper.start(), RunOutcome::Aborted);
+        assert_eq!(wrapper.get_statistics().get_steps(), 0);
+        stopper.join().unwrap();
+    }
+}
@@ -1,29 +1,139 @@
+use std::cell::Cell;
 use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::Duration;
 
-use crate::{execution::Heading, generation::Maze, Point};
+use crate::{execution::Heading, generation::Maze, solving::{path_solver::ShortestPathSolver, Solver}, Point};
 
-use super::{Tile, Robot, private};
+use super::{
+    map::{MapCell, RobotMap},
+    energy::{Battery, EnergyModel},
+    noise::{Noise, NoiseModel},
+    private,
+    rules::{self, Inventory, Move, RunState},
+    sensors::{self, LocalView, MazeRef, SensorConfig, SensorError},
+    trail::VisitTrail,
+    Facing, Robot, Tile, ROBOT_HEALTH, START_HEADING,
+};
 
 pub struct ThreadedRobot {
     location: Point,
     target_loc: Point,
     heading: Heading,
     maze: Arc<RwLock<Maze<Tile>>>,
+    trail: Arc<RwLock<VisitTrail>>,
+    map: Arc<RwLock<RobotMap>>,
+    state: RunState,
     steps: i64,
     collisions: i64,
+    turns: i64,
+    time: u64,
+    health: u32,
+    sensor_reads: Cell<i64>,
+    range_limit: Option<usize>,
+    noise: Option<Noise>,
+    battery: Battery,
+    sensors: SensorConfig,
+    fog_of_war: bool,
+    shortest_path: Option<usize>,
     runs: i32,
 }
 
 impl ThreadedRobot {
-    pub fn get_maze(&self) -> Arc<RwLock<Maze<Tile>>> {
+    /// Shared handle to the maze the robot is in
+    pub fn get_maze_ref(&self) -> Arc<RwLock<Maze<Tile>>> {
         self.maze.clone()
     }
 
+    /// Shared handle to the robot's trail. The handle stays valid when the
+    /// maze is changed, so it can be held onto by a renderer.
+    pub fn get_trail_ref(&self) -> Arc<RwLock<VisitTrail>> {
+        self.trail.clone()
+    }
+
+    /// Shared handle to the map the robot has built, for drawing only
+    /// what it has discovered. Stays valid when the maze is changed.
+    pub fn get_map_ref(&self) -> Arc<RwLock<RobotMap>> {
+        self.map.clone()
+    }
+
+    /// Should renderers only draw what the robot has discovered
+    pub fn get_fog_of_war(&self) -> bool {
+        self.fog_of_war
+    }
+
+    /// The tile a renderer should draw at `point`, as it currently appears.
+    /// `None` off the maze, or where fog of war hides a cell the robot hasn't sensed.
+    pub fn get_shown_tile(&self, point: Point) -> Option<Tile> {
+        if self.fog_of_war && !self.map.read().is_ok_and(|map| map.is_known(point)) {
+            return None;
+        }
+        match self.maze.read() {
+            Ok(maze) => maze.get_cell(point).map(|tile| self.state.effective_tile(*tile)),
+            Err(_) => unreachable!()
+        }
+    }
+
     pub(crate) fn set_maze(&mut self, maze: Arc<RwLock<Maze<Tile>>>) {
         self.location = maze.read().unwrap().get_start();
         self.maze = maze;
+        self.runs = 0;
+        self.reset_map();
+        self.reset_run_state();
+    }
+
+    /// Where the robot is trying to get to
+    fn goal(&self) -> Point {
+        match self.maze.read() {
+            Ok(maze) => maze.get_finish(),
+            Err(_) => unreachable!()
+        }
+    }
+
+    /// Forget everything the robot has sensed and find the shortest route, for a new maze
+    fn reset_map(&mut self) {
+        let (width, height) = match self.maze.read() {
+            Ok(maze) => {
+                self.shortest_path = ShortestPathSolver::new().solve(&maze).map(|solution| solution.get_steps());
+                (maze.get_width(), maze.get_height())
+            }
+            Err(_) => unreachable!()
+        };
+
+        if let Ok(mut map) = self.map.write() {
+            map.resize(width, height);
+            map.mark(self.location, MapCell::Open);
+        }
+    }
+
+    /// Record something sensed on the map
+    fn update_map(&self, update: impl FnOnce(&mut RobotMap)) {
+        if let Ok(mut map) = self.map.write() {
+            update(&mut map);
+        }
+    }
+
+    /// Clear the counters, trail and anything picked up for a fresh run
+    fn reset_run_state(&mut self) {
+        self.steps = 0;
+        self.collisions = 0;
+        self.turns = 0;
+        self.time = 0;
+        self.state = RunState::default();
+        self.health = ROBOT_HEALTH;
+        self.heading = START_HEADING;
+        self.sensor_reads.set(0);
+        self.battery.recharge();
+
+        let (width, height) = match self.maze.read() {
+            Ok(maze) => (maze.get_width(), maze.get_height()),
+            Err(_) => unreachable!()
+        };
+
+        if let Ok(mut trail) = self.trail.write() {
+            trail.resize(width, height);
+            trail.visit(self.location);
+        }
     }
 }
 
@@ -32,10 +142,23 @@ impl Default for ThreadedRobot {
         Self {
             location: Point(1, 1),
             target_loc: Point(1, 1),
-            heading: Heading::East,
+            heading: START_HEADING,
             maze: Arc::from(RwLock::from(Maze::new(15, 15))),
+            trail: Arc::from(RwLock::from(VisitTrail::new(15, 15))),
+            map: Arc::from(RwLock::from(RobotMap::new(15, 15))),
+            state: RunState::default(),
             steps: 0,
             collisions: 0,
+            turns: 0,
+            time: 0,
+            health: ROBOT_HEALTH,
+            sensor_reads: Cell::new(0),
+            range_limit: None,
+            noise: None,
+            battery: Battery::default(),
+            sensors: SensorConfig::default(),
+            fog_of_war: false,
+            shortest_path: None,
             runs: 0,
         }
     }
@@ -44,29 +167,85 @@ impl Default for ThreadedRobot {
 impl Robot for ThreadedRobot {
     type Tiles = Tile;
 
-    fn face(&mut self, face: super::Facing) {
-        self.set_heading(self.heading.augment_heading(face))
+    fn face(&mut self, face: Facing) {
+        self.battery.face(face);
+        if face != Facing::Ahead {
+            self.turns += 1;
+        }
+        self.heading = self.heading.augment_heading(face)
+    }
+
+    fn look(&self, face: Facing) -> Self::Tiles {
+        let heading = self.heading.augment_heading(face);
+        self.sensor_reads.set(self.sensor_reads.get() + 1);
+        self.battery.sense();
+
+        let tile = match self.maze.read() {
+            Ok(maze) => heading
+                .next_point(self.location)
+                .and_then(|pos| maze.get_cell(pos))
+                .map_or(Tile::Wall, |tile| self.state.effective_tile(*tile)),
+            Err(_) => unreachable!()
+        };
+
+        let tile = match &self.noise {
+            Some(noise) => noise.look(tile),
+            None => tile,
+        };
+        self.update_map(|map| map.record_look(self.location, heading, &tile));
+        tile
     }
 
-    fn look(&self, face: super::Facing) -> Self::Tiles {
+    /// Count the open cells in the relative facing direction
+    fn range(&self, face: Facing) -> usize {
         let heading = self.heading.augment_heading(face);
-        let Point(locx, locy) = self.get_location();
-        let pos = match heading { // Panic waiting to happen with these subs if the robot tries to look off the maze
-            Heading::North => Point(locx, locy - 1),
-            Heading::East => Point(locx + 1, locy),
-            Heading::South => Point(locx, locy + 1),
-            Heading::West => Point(locx - 1, locy),
+        self.sensor_reads.set(self.sensor_reads.get() + 1);
+        self.battery.sense();
+
+        let range = match self.maze.read() {
+            Ok(maze) => rules::range(&maze, &self.state, self.location, heading, self.range_limit),
+            Err(_) => unreachable!()
         };
 
+        let range = match &self.noise {
+            Some(noise) => noise.range(range),
+            None => range,
+        };
+        self.update_map(|map| map.record_range(self.location, heading, range, self.range_limit));
+        range
+    }
+
+    fn get_maze(&self) -> Result<MazeRef<'_, Self::Tiles>, SensorError> {
+        if !self.sensors.full_map {
+            return Err(SensorError::NoMap);
+        }
         match self.maze.read() {
-            Ok(maze) => *maze.get_cell(pos).unwrap_or(&Tile::Wall),
+            Ok(maze) => Ok(MazeRef::Locked(maze)),
             Err(_) => unreachable!()
         }
-        // *self.get_maze().get_cell(pos).unwrap_or(&Tile::Wall)
     }
 
-    fn get_maze(&self) -> &Maze<Self::Tiles> {
-        unimplemented!()
+    /// Get the tiles around the robot
+    fn get_local_view(&self) -> Result<LocalView<Self::Tiles>, SensorError> {
+        let radius = self.sensors.view_radius.ok_or(SensorError::NoLocalView)?;
+        let view = match self.maze.read() {
+            Ok(maze) => LocalView::new(&maze, &self.state, self.location, self.heading, radius),
+            Err(_) => unreachable!()
+        };
+        self.update_map(|map| map.record_view(&view, self.location, self.heading));
+        Ok(view)
+    }
+
+    /// Get the direction of the goal relative to the robot
+    fn get_goal_bearing(&self) -> Result<f64, SensorError> {
+        if !self.sensors.goal_bearing {
+            return Err(SensorError::NoGoalBearing);
+        }
+        Ok(sensors::bearing(self.location, self.goal(), self.heading))
+    }
+
+    fn at_goal(&self) -> bool {
+        self.location == self.goal()
     }
     
     /// Sleep for a bit. Time is a millisecond value.
@@ -80,24 +259,75 @@ impl Robot for ThreadedRobot {
 
     fn get_collisions(&self) -> i64 { self.collisions }
 
+    fn get_turns(&self) -> i64 { self.turns }
+
+    fn get_time(&self) -> u64 { self.time }
+
+    fn get_sensor_reads(&self) -> i64 { self.sensor_reads.get() }
+
+    fn get_health(&self) -> u32 { self.health }
+
+    fn get_energy(&self) -> Option<u64> { self.battery.remaining() }
+
+    fn get_energy_used(&self) -> u64 { self.battery.used() }
+
+    fn get_inventory(&self) -> Inventory { self.state.get_inventory() }
+
+    fn get_trail(&self) -> VisitTrail {
+        match self.trail.read() {
+            Ok(trail) => trail.clone(),
+            Err(_) => unreachable!()
+        }
+    }
+
+    fn get_map(&self) -> Result<RobotMap, SensorError> {
+        if !self.sensors.discovered_map {
+            return Err(SensorError::NoDiscoveredMap);
+        }
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        match self.map.read() {
+            Ok(map) => Ok(map.clone()),
+            Err(_) => unreachable!()
+        }
+    }
+
     /// Set the robots absolute heading
-    fn set_heading(&mut self, heading: Heading) {
-        self.heading = heading
+    fn set_heading(&mut self, heading: Heading) -> Result<(), SensorError> {
+        if !self.sensors.compass {
+            return Err(SensorError::NoCompass);
+        }
+        self.battery.turn(self.heading, heading);
+        if heading != self.heading {
+            self.turns += 1;
+        }
+        self.heading = heading;
+        Ok(())
     }
 
     /// Get the robots current heading
-    fn get_heading(&self) -> Heading {
-        self.heading
+    fn get_heading(&self) -> Result<Heading, SensorError> {
+        if !self.sensors.compass {
+            return Err(SensorError::NoCompass);
+        }
+        Ok(self.heading)
     }
 
     /// Get the location of the robot
-    fn get_goal_location(&self) -> Point {
-        self.maze.read().unwrap().get_finish()
+    fn get_goal_location(&self) -> Result<Point, SensorError> {
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        Ok(self.goal())
     }
     
     /// Get the current location of the robot
-    fn get_location(&self) -> Point {
-        self.location
+    fn get_location(&self) -> Result<Point, SensorError> {
+        if !self.sensors.position {
+            return Err(SensorError::NoPosition);
+        }
+        Ok(self.location)
     }
 }
 
@@ -110,6 +340,9 @@ impl private::Robot for ThreadedRobot {
 
         // println!("set target {:?}; start {:?}", self.get_goal_location(), self.get_location());
         self.maze = Arc::from(RwLock::new(maze.as_ref().clone()));
+        self.runs = 0;
+        self.reset_map();
+        self.reset_run_state();
     }
 
     /// Set the current location of the robot
@@ -117,15 +350,15 @@ impl private::Robot for ThreadedRobot {
         self.location = loc
     }
 
-    /// Reset everything
+    /// Put the robot back at the start for another run.
+    /// Anything it has mapped is remembered.
     fn reset(&mut self) {
         self.location = match self.maze.read() {
             Ok(res) => res.get_start(),
             Err(_) => unreachable!()
         };
         self.runs += 1;
-        self.steps = 0;
-        self.collisions = 0;
+        self.reset_run_state();
     }
 
     /// Set where the robots target is
@@ -133,41 +366,93 @@ impl private::Robot for ThreadedRobot {
         self.target_loc = loc
     }
 
+    fn set_range_limit(&mut self, limit: Option<usize>) {
+        self.range_limit = limit
+    }
+
+    fn set_noise(&mut self, noise: Option<NoiseModel>) {
+        self.noise = noise.map(Noise::new)
+    }
+
+    fn set_energy(&mut self, energy: Option<EnergyModel>) {
+        self.battery = Battery::new(energy)
+    }
+
+    fn set_sensors(&mut self, sensors: SensorConfig) {
+        self.sensors = sensors
+    }
+
+    fn set_fog_of_war(&mut self, fog: bool) {
+        self.fog_of_war = fog
+    }
+
+    fn shortest_path(&self) -> Option<usize> {
+        self.shortest_path
+    }
+
+    fn current_location(&self) -> Point {
+        self.location
+    }
+
+    fn current_heading(&self) -> Heading {
+        self.heading
+    }
+
     /// Advance the robot on. Define at crate level privacy
     /// to stop an external robot controller calling this at the wrong time
     /// Should be implemented by the concrete Robot impl.
-    // BUG: This will panic if robot tries to exit off the top or left side of the maze. 
     fn advance(&mut self) {
+        if self.is_destroyed() || self.is_out_of_energy() {
+            return;
+        }
 
-        let Point(locx, locy) = self.get_location();
-        // println!("{:?} {:?}", locx, locy);
-        let new_loc = match self.get_heading() {
-            Heading::North => Point(locx, locy - 1),
-            Heading::East => Point(locx + 1, locy),
-            Heading::South => Point(locx, locy + 1),
-            Heading::West => Point(locx - 1, locy)
+        let heading = match &self.noise {
+            Some(noise) => noise.heading(self.heading),
+            None => Some(self.heading),
+        };
+        let Some(heading) = heading else {
+            // Stalled, the wheels spun without going anywhere
+            self.time += 1;
+            self.battery.movement(1);
+            return;
         };
-        // println!("heading {:?}", self.get_heading());
-        // println!("new pos {:?}\t old pos {:?}", new_loc, Point(locx, locy));
 
-        match self.maze.read() {
-            Ok(maze) => {
-                if maze.can_move(new_loc) {
-                    self.steps += 1;
-                    self.location = new_loc;
-                } else {
-                    self.collisions += 1;
-                }
-            },
+        let result = match self.maze.read() {
+            Ok(maze) => rules::resolve_move(&maze, &mut self.state, self.location, heading),
             Err(_) => unreachable!()
-        }
+        };
 
-        match self.maze.write() {
-            Ok(mut maze) => {
-                maze.set_cell(Point(locx, locy), Tile::BeenBefore)
-            }, 
-            Err(_) => ()
+        match result {
+            Move::Moved { to, cost, damage } => {
+                self.steps += 1;
+                self.time += cost as u64;
+                self.battery.movement(cost as u64);
+                self.health = self.health.saturating_sub(damage);
+                self.location = to;
+                if let Ok(mut trail) = self.trail.write() {
+                    trail.visit(to);
+                }
+                self.update_map(|map| map.mark(to, MapCell::Open));
+            }
+            Move::Destroyed { to, cost } => {
+                self.steps += 1;
+                self.time += cost as u64;
+                self.battery.movement(cost as u64);
+                self.health = 0;
+                self.location = to;
+                if let Ok(mut trail) = self.trail.write() {
+                    trail.visit(to);
+                }
+                self.update_map(|map| map.mark(to, MapCell::Open));
+            }
+            Move::Blocked => {
+                if let Some(wall) = heading.next_point(self.location) {
+                    self.update_map(|map| map.mark(wall, MapCell::Wall));
+                }
+                self.collisions += 1;
+                self.time += 1;
+                self.battery.collision();
+            }
         }
-        
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,322 @@
+use std::{error::Error, fmt, fs, io, path::Path, str::FromStr};
+
+use crate::Point;
+
+use super::{polled_controller::PolledController, Heading, Robot, RunOutcome};
+
+/// What happened on a single tick of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceTick {
+    /// Heading the controller left the robot facing
+    pub heading: Heading,
+    /// Number of `look` and `range` calls the controller made
+    pub sensor_reads: i64,
+    /// Where the robot was after moving
+    pub position: Point,
+    /// Did the robot bump into a wall
+    pub collided: bool,
+}
+
+/// Record of every tick of a run, so it can be looked over or replayed later.
+///
+/// Saved as plain text, one tick per line:
+/// ```text
+/// maze-trace 1
+/// start 1 1 E
+/// E 2 1 3 .
+/// E 2 1 1 x
+/// end ReachedGoal
+/// ```
+/// Each tick is the heading, position, sensor reads and `x` for a collision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunTrace {
+    start: Point,
+    heading: Heading,
+    ticks: Vec<TraceTick>,
+    outcome: Option<RunOutcome>,
+}
+
+impl RunTrace {
+    /// Start a trace for a robot at `start` facing `heading`
+    pub fn new(start: Point, heading: Heading) -> Self {
+        RunTrace {
+            start,
+            heading,
+            ticks: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    pub fn get_start(&self) -> Point {
+        self.start
+    }
+
+    /// Heading the robot was facing before the first tick
+    pub fn get_heading(&self) -> Heading {
+        self.heading
+    }
+
+    pub fn get_ticks(&self) -> &[TraceTick] {
+        &self.ticks
+    }
+
+    /// How the run ended, `None` if the trace was cut short
+    pub fn get_outcome(&self) -> Option<RunOutcome> {
+        self.outcome
+    }
+
+    pub(crate) fn push(&mut self, tick: TraceTick) {
+        self.ticks.push(tick)
+    }
+
+    pub(crate) fn finish(&mut self, outcome: RunOutcome) {
+        self.outcome = Some(outcome)
+    }
+
+    /// Check another trace is the same as this one, saying where they first differ
+    pub fn verify(&self, actual: &RunTrace) -> Result<(), TraceMismatch> {
+        self.compare(actual, |expected, actual| expected == actual)
+    }
+
+    /// Check a run made by a `ReplayController` follows this trace. The
+    /// replay doesn't read any sensors, so sensor reads aren't compared.
+    pub fn verify_replay(&self, actual: &RunTrace) -> Result<(), TraceMismatch> {
+        self.compare(actual, |expected, actual| TraceTick { sensor_reads: 0, ..*expected } == TraceTick { sensor_reads: 0, ..*actual })
+    }
+
+    fn compare(&self, actual: &RunTrace, same: impl Fn(&TraceTick, &TraceTick) -> bool) -> Result<(), TraceMismatch> {
+        if (self.start, self.heading) != (actual.start, actual.heading) {
+            return Err(TraceMismatch::Start);
+        }
+
+        let ticks = self.ticks.len().max(actual.ticks.len());
+        let differs = |tick: &usize| match (self.ticks.get(*tick), actual.ticks.get(*tick)) {
+            (Some(expected), Some(actual)) => !same(expected, actual),
+            _ => true,
+        };
+        match (0..ticks).find(differs) {
+            Some(tick) => Err(TraceMismatch::Tick {
+                tick,
+                expected: self.ticks.get(tick).copied(),
+                actual: actual.ticks.get(tick).copied(),
+            }),
+            None if self.outcome != actual.outcome => Err(TraceMismatch::Outcome),
+            None => Ok(()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl fmt::Display for RunTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "maze-trace 1")?;
+        writeln!(f, "start {} {} {}", self.start.get_x(), self.start.get_y(), heading_symbol(self.heading))?;
+        for tick in &self.ticks {
+            writeln!(
+                f,
+                "{} {} {} {} {}",
+                heading_symbol(tick.heading),
+                tick.position.get_x(),
+                tick.position.get_y(),
+                tick.sensor_reads,
+                if tick.collided { 'x' } else { '.' }
+            )?;
+        }
+        if let Some(outcome) = self.outcome {
+            writeln!(f, "end {:?}", outcome)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RunTrace {
+    type Err = TraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty());
+        if lines.next().map(|(_, line)| line) != Some("maze-trace 1") {
+            return Err(TraceError::BadHeader);
+        }
+
+        let mut trace = match lines.next() {
+            Some((number, line)) => match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["start", x, y, heading] => RunTrace::new(
+                    Point(parse_field(x, number)?, parse_field(y, number)?),
+                    parse_heading(heading).ok_or(TraceError::BadLine(number + 1))?,
+                ),
+                _ => return Err(TraceError::BadLine(number + 1)),
+            },
+            None => return Err(TraceError::BadHeader),
+        };
+
+        for (number, line) in lines {
+            if trace.outcome.is_some() {
+                return Err(TraceError::BadLine(number + 1));
+            }
+
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["end", outcome] => trace.finish(parse_outcome(outcome).ok_or(TraceError::BadLine(number + 1))?),
+                [heading, x, y, reads, collided] => trace.push(TraceTick {
+                    heading: parse_heading(heading).ok_or(TraceError::BadLine(number + 1))?,
+                    position: Point(parse_field(x, number)?, parse_field(y, number)?),
+                    sensor_reads: parse_field(reads, number)?,
+                    collided: match collided {
+                        "x" => true,
+                        "." => false,
+                        _ => return Err(TraceError::BadLine(number + 1)),
+                    },
+                }),
+                _ => return Err(TraceError::BadLine(number + 1)),
+            }
+        }
+
+        Ok(trace)
+    }
+}
+
+fn parse_field<T: FromStr>(value: &str, number: usize) -> Result<T, TraceError> {
+    value.parse().map_err(|_| TraceError::BadLine(number + 1))
+}
+
+pub(crate) fn heading_symbol(heading: Heading) -> char {
+    match heading {
+        Heading::North => 'N',
+        Heading::East => 'E',
+        Heading::South => 'S',
+        Heading::West => 'W',
+    }
+}
+
+pub(crate) fn parse_heading(value: &str) -> Option<Heading> {
+    Heading::ALL.into_iter().find(|heading| value == heading_symbol(*heading).to_string())
+}
+
+fn parse_outcome(value: &str) -> Option<RunOutcome> {
+    [
+        RunOutcome::ReachedGoal,
+        RunOutcome::Destroyed,
+        RunOutcome::OutOfSteps,
+        RunOutcome::TooManyCollisions,
+        RunOutcome::TimedOut,
+        RunOutcome::OutOfEnergy,
+        RunOutcome::Aborted,
+    ]
+    .into_iter()
+    .find(|outcome| format!("{:?}", outcome) == value)
+}
+
+/// A trace file couldn't be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceError {
+    /// The file doesn't start with a trace header and start line
+    BadHeader,
+    /// This line (counting from one) couldn't be understood
+    BadLine(usize),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::BadHeader => write!(f, "not a maze trace"),
+            TraceError::BadLine(line) => write!(f, "couldn't read line {} of the trace", line),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+/// Where a run stopped matching the trace it was checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceMismatch {
+    /// The robot started somewhere else or facing a different way
+    Start,
+    /// The runs differ on this tick. `None` is a run which had already ended.
+    Tick { tick: usize, expected: Option<TraceTick>, actual: Option<TraceTick> },
+    /// Every tick matched but the runs ended differently
+    Outcome,
+}
+
+impl fmt::Display for TraceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceMismatch::Start => write!(f, "the runs started differently"),
+            TraceMismatch::Tick { tick, expected, actual } => {
+                write!(f, "tick {} differs: expected {:?}, got {:?}", tick, expected, actual)
+            }
+            TraceMismatch::Outcome => write!(f, "the runs ended differently"),
+        }
+    }
+}
+
+impl Error for TraceMismatch {}
+
+/// Controller which turns the robot the same way as a recorded run,
+/// so the run can be watched again through the usual callback or renderer.
+/// Only needs the robot to turn, so works whatever sensors are allowed.
+/// Each run replays the trace again from the start. It makes no sensor
+/// reads, so check replays with `RunTrace::verify_replay`.
+#[derive(Default)]
+pub struct ReplayController {
+    headings: Vec<Heading>,
+    /// Heading the recorded run started with
+    start: Option<Heading>,
+    heading: Option<Heading>,
+    tick: usize,
+}
+
+impl ReplayController {
+    pub fn new(trace: &RunTrace) -> Self {
+        ReplayController {
+            headings: trace.ticks.iter().map(|tick| tick.heading).collect(),
+            start: Some(trace.heading),
+            heading: Some(trace.heading),
+            tick: 0,
+        }
+    }
+}
+
+impl<R: Robot> PolledController<R> for ReplayController {
+    fn control_robot(&mut self, robot: &mut R) {
+        if let (Some(current), Some(next)) = (self.heading, self.headings.get(self.tick)) {
+            robot.face(current.facing_towards(*next));
+            self.heading = Some(*next);
+        }
+        self.tick += 1;
+    }
+
+    fn reset(&mut self) {
+        self.heading = self.start;
+        self.tick = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_round_trips_through_text() {
+        let mut trace = RunTrace::new(Point(1, 1), Heading::East);
+        trace.push(TraceTick { heading: Heading::East, sensor_reads: 3, position: Point(2, 1), collided: false });
+        trace.push(TraceTick { heading: Heading::North, sensor_reads: 0, position: Point(2, 1), collided: true });
+        trace.finish(RunOutcome::OutOfSteps);
+
+        let text = trace.to_string();
+        assert_eq!(text, "maze-trace 1\nstart 1 1 E\nE 2 1 3 .\nN 2 1 0 x\nend OutOfSteps\n");
+        assert_eq!(text.parse(), Ok(trace.clone()));
+        assert_eq!("maze-trace 1\nstart 1 1 Q\n".parse::<RunTrace>(), Err(TraceError::BadLine(2)));
+
+        let mut other = trace.clone();
+        other.ticks[1].collided = false;
+        assert!(matches!(trace.verify(&other), Err(TraceMismatch::Tick { tick: 1, .. })));
+    }
+}
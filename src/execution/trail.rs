@@ -0,0 +1,70 @@
+use crate::Point;
+
+/// Record of where a robot has been during a run. Kept separately from the
+/// maze so the maze itself is never modified while a robot is running.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VisitTrail {
+    width: usize,
+    height: usize,
+    visits: Vec<Vec<u32>>,
+}
+
+impl VisitTrail {
+    /// Create an empty trail covering a maze of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        VisitTrail {
+            width,
+            height,
+            visits: vec![vec![0; width]; height],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    /// Note that the robot has entered this cell
+    pub(crate) fn visit(&mut self, point: Point) {
+        if let Some(count) = self.visits.get_mut(point.get_y()).and_then(|row| row.get_mut(point.get_x())) {
+            *count += 1;
+        }
+    }
+
+    /// Forget every visit, keeping the size of the trail
+    pub(crate) fn clear(&mut self) {
+        self.visits.iter_mut().flatten().for_each(|count| *count = 0);
+    }
+
+    /// Clear and resize the trail for a new maze
+    pub(crate) fn resize(&mut self, width: usize, height: usize) {
+        *self = VisitTrail::new(width, height);
+    }
+
+    /// Number of times the robot has entered the cell
+    pub fn get_visits(&self, point: Point) -> u32 {
+        self.visits
+            .get(point.get_y())
+            .and_then(|row| row.get(point.get_x()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Has the robot been in this cell at all
+    pub fn has_visited(&self, point: Point) -> bool {
+        self.get_visits(point) > 0
+    }
+
+    /// Number of distinct cells the robot has been in
+    pub fn unique_cells(&self) -> usize {
+        self.visits.iter().flatten().filter(|count| **count > 0).count()
+    }
+
+    /// Number of times the robot entered a cell it had already been in
+    pub fn revisits(&self) -> u32 {
+        self.visits.iter().flatten().map(|count| count.saturating_sub(1)).sum()
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{polled_controller::PolledController, Facing, Heading, Robot, TileType};
+
+/// A cell relative to where the robot started
+type Cell = (i64, i64);
+
+/// Trémaux's algorithm. Marks each passage as it goes along it, never goes
+/// down a passage marked twice, and turns back when it comes into somewhere
+/// it has been before along a new passage. Reaches the goal of any maze
+/// where it can be reached, otherwise wanders until the run's limits stop it.
+///
+/// Only uses `look` and `face`, so it keeps track of where it is itself,
+/// assuming every move onto an open tile takes the robot one tile along.
+/// Ice and teleporters will confuse it.
+#[derive(Debug, Clone)]
+pub struct TremauxController {
+    position: Cell,
+    /// Which way the robot is facing, relative to how it started
+    heading: Heading,
+    /// Did the robot face an open passage last tick, so has since moved along it
+    moving: bool,
+    visited: HashSet<Cell>,
+    /// Times each passage between two cells has been gone along
+    marks: HashMap<(Cell, Cell), u8>,
+}
+
+impl Default for TremauxController {
+    fn default() -> Self {
+        TremauxController {
+            position: (0, 0),
+            heading: Heading::North,
+            moving: false,
+            visited: HashSet::new(),
+            marks: HashMap::new(),
+        }
+    }
+}
+
+impl TremauxController {
+    fn neighbour(cell: Cell, heading: Heading) -> Cell {
+        let (x, y) = cell;
+        match heading {
+            Heading::North => (x, y - 1),
+            Heading::East => (x + 1, y),
+            Heading::South => (x, y + 1),
+            Heading::West => (x - 1, y),
+        }
+    }
+
+    fn passage(&self, face: Facing) -> (Cell, Cell) {
+        let next = Self::neighbour(self.position, self.heading.augment_heading(face));
+        (self.position.min(next), self.position.max(next))
+    }
+
+    /// Times the passage off to the `face` side has been gone along
+    pub fn get_marks(&self, face: Facing) -> u8 {
+        self.marks.get(&self.passage(face)).copied().unwrap_or(0)
+    }
+}
+
+impl<R: Robot> PolledController<R> for TremauxController {
+    fn control_robot(&mut self, robot: &mut R) {
+        if self.moving {
+            self.position = Self::neighbour(self.position, self.heading);
+        }
+        let first_visit = self.visited.insert(self.position);
+
+        let open: Vec<Facing> = [Facing::Ahead, Facing::Right, Facing::Left, Facing::Behind]
+            .into_iter()
+            .filter(|face| robot.look(*face).can_walk())
+            .collect();
+
+        let face = if self.moving && !first_visit && self.get_marks(Facing::Behind) == 1 {
+            Some(Facing::Behind)
+        } else {
+            open.into_iter().min_by_key(|face| self.get_marks(*face))
+        };
+
+        self.moving = face.is_some();
+        if let Some(face) = face {
+            let marks = self.marks.entry(self.passage(face)).or_default();
+            *marks = marks.saturating_add(1);
+            self.heading = self.heading.augment_heading(face);
+            robot.face(face);
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution::{
+            limits::RunLimits, polled_controller::PolledControllerWrapper, robot::DefaultRobot, sensors::SensorConfig,
+            wall_follower_controller::WallFollowerController, Controller, RunOutcome, Tile,
+        },
+        generation::{actual_prim_generator::GappedPrimGenerator, Generator, Maze},
+        Point,
+    };
+
+    #[test]
+    fn finds_goal_wall_following_misses() {
+        // Goal in the middle of an open room, away from every wall
+        let mut room: Maze<Tile> = Maze::new(9, 9);
+        for i in 0..9 {
+            for edge in [Point(i, 0), Point(i, 8), Point(0, i), Point(8, i)] {
+                room.set_cell(edge, Tile::Wall);
+            }
+        }
+        room.set_start(Point(1, 1));
+        room.set_finish(Point(4, 4));
+        let limits = RunLimits { max_steps: Some(5_000), max_collisions: Some(10), ..Default::default() };
+
+        let mut follower = PolledControllerWrapper::<DefaultRobot, WallFollowerController>::new();
+        follower.set_limits(limits);
+        follower.set_maze(room.clone());
+        assert_eq!(follower.start(), RunOutcome::OutOfSteps);
+
+        for maze in [room, GappedPrimGenerator::new().generate_maze()] {
+            let mut wrapper = PolledControllerWrapper::<DefaultRobot, TremauxController>::new();
+            wrapper.set_sensors(SensorConfig::blind());
+            wrapper.set_limits(limits);
+            wrapper.set_maze(maze);
+            assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+            assert_eq!(wrapper.get_statistics().get_collisions(), 0);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use super::{polled_controller::PolledController, Facing, Robot, TileType};
+
+/// Which hand a controller keeps on the wall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Hand {
+    Left,
+    #[default]
+    Right,
+}
+
+impl Hand {
+    /// Facing towards the side of the hand
+    pub fn facing(&self) -> Facing {
+        match self {
+            Hand::Left => Facing::Left,
+            Hand::Right => Facing::Right,
+        }
+    }
+
+    /// Facing towards the other side
+    pub fn other(&self) -> Facing {
+        match self {
+            Hand::Left => Facing::Right,
+            Hand::Right => Facing::Left,
+        }
+    }
+}
+
+/// Keeps one hand on the wall, turning towards that hand whenever it can.
+/// Only uses `look` and `face`. Reaches the goal of any maze without loops,
+/// but can go round a loop forever or circle in open space.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WallFollowerController {
+    hand: Hand,
+}
+
+impl WallFollowerController {
+    pub fn new(hand: Hand) -> Self {
+        WallFollowerController { hand }
+    }
+
+    pub fn get_hand(&self) -> Hand {
+        self.hand
+    }
+}
+
+impl<R: Robot> PolledController<R> for WallFollowerController {
+    fn control_robot(&mut self, robot: &mut R) {
+        let face = [self.hand.facing(), Facing::Ahead, self.hand.other()]
+            .into_iter()
+            .find(|face| robot.look(*face).can_walk())
+            .unwrap_or(Facing::Behind);
+        robot.face(face);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution::{limits::RunLimits, polled_controller::PolledControllerWrapper, robot::DefaultRobot, sensors::SensorConfig, Controller, RunOutcome},
+        generation::{actual_prim_generator::GappedPrimGenerator, Generator},
+    };
+
+    #[test]
+    fn solves_prim_maze_with_either_hand() {
+        for hand in [Hand::Left, Hand::Right] {
+            let mut wrapper = PolledControllerWrapper::<DefaultRobot, _>::with_controller(WallFollowerController::new(hand));
+            wrapper.set_sensors(SensorConfig::blind());
+            wrapper.set_limits(RunLimits { max_steps: Some(10_000), max_collisions: Some(10), ..Default::default() });
+            wrapper.set_maze(GappedPrimGenerator::new().generate_maze());
+            assert_eq!(wrapper.start(), RunOutcome::ReachedGoal);
+        }
+    }
+}
@@ -62,7 +62,7 @@ impl Generator for GappedPrimGenerator {
         frontier.push(Point(ix, iy));
         
         while let Some(Point(x, y)) = {
-            random_index = if frontier.len() > 0 {
+            random_index = if !frontier.is_empty() {
                 thread_rng.gen_range(0..frontier.len())
             } else {
                 0
@@ -163,7 +163,7 @@ impl GappedPrimGenerator {
     pub fn connect_random_neighbour(&mut self, point: Point) {
         let neighbours = self.get_neighbours_around_point(point);
         
-        if neighbours.len() == 0 {
+        if neighbours.is_empty() {
             return;
         }
 
@@ -186,7 +186,7 @@ fn test() {
             print!("{}", match j {
                 Tile::Wall => "#",
                 Tile::Passage => " ",
-                Tile::BeenBefore => "*"
+                _ => "*"
             });
         }
         println!()
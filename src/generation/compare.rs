@@ -0,0 +1,279 @@
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use crate::execution::TileType;
+use crate::Point;
+
+use super::Maze;
+
+/// The eight symmetries of a rectangle (the dihedral group D4).
+/// Rotations are clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Whether this symmetry swaps the width and height of the maze
+    pub fn swaps_axes(&self) -> bool {
+        matches!(
+            self,
+            Symmetry::Rotate90 | Symmetry::Rotate270 | Symmetry::Transpose | Symmetry::AntiTranspose
+        )
+    }
+
+    /// Map a point in a width x height grid onto the transformed grid
+    pub fn apply(&self, point: Point, width: usize, height: usize) -> Point {
+        let Point(x, y) = point;
+        match self {
+            Symmetry::Identity => Point(x, y),
+            Symmetry::Rotate90 => Point(height - 1 - y, x),
+            Symmetry::Rotate180 => Point(width - 1 - x, height - 1 - y),
+            Symmetry::Rotate270 => Point(y, width - 1 - x),
+            Symmetry::FlipHorizontal => Point(width - 1 - x, y),
+            Symmetry::FlipVertical => Point(x, height - 1 - y),
+            Symmetry::Transpose => Point(y, x),
+            Symmetry::AntiTranspose => Point(height - 1 - y, width - 1 - x),
+        }
+    }
+}
+
+/// A single cell which differs between two mazes.
+/// `None` means the cell is outside of that maze.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange<T> {
+    pub point: Point,
+    pub before: Option<T>,
+    pub after: Option<T>,
+}
+
+/// Structural differences between two mazes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeDiff<T> {
+    /// (before, after) dimensions as (width, height) if they changed
+    pub size: Option<((usize, usize), (usize, usize))>,
+    /// (before, after) start point if it moved
+    pub start: Option<(Point, Point)>,
+    /// (before, after) goal point if it moved
+    pub goal: Option<(Point, Point)>,
+    pub cells: Vec<CellChange<T>>,
+}
+
+impl<T> MazeDiff<T> {
+    /// True when the two mazes were identical
+    pub fn is_empty(&self) -> bool {
+        self.size.is_none() && self.start.is_none() && self.goal.is_none() && self.cells.is_empty()
+    }
+}
+
+/// Borrowed view of a maze which compares and hashes with
+/// any robot marks (eg. `Tile::BeenBefore`) ignored.
+/// Useful for deduplicating mazes in a `HashSet`.
+#[derive(Debug, Clone, Copy)]
+pub struct Unmarked<'a, T: TileType + Default>(pub &'a Maze<T>);
+
+impl<'a, T: TileType + Default + Clone + PartialEq> PartialEq for Unmarked<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (self.0, other.0);
+        a.width == b.width
+            && a.height == b.height
+            && a.start == b.start
+            && a.goal == b.goal
+            && a.grid.iter().flatten().zip(b.grid.iter().flatten()).all(|(x, y)| x.unmarked() == y.unmarked())
+    }
+}
+
+impl<'a, T: TileType + Default + Clone + Eq> Eq for Unmarked<'a, T> {}
+
+impl<'a, T: TileType + Default + Clone + Hash> Hash for Unmarked<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let maze = self.0;
+        maze.width.hash(state);
+        maze.height.hash(state);
+        maze.start.hash(state);
+        maze.goal.hash(state);
+        for tile in maze.grid.iter().flatten() {
+            tile.unmarked().hash(state);
+        }
+    }
+}
+
+/// FNV-1a, used for fingerprints. Only ever fed fixed width little endian
+/// values and tile codes, never anything through `Hash`, so fingerprints
+/// are the same on every platform and build and can be stored alongside mazes.
+struct FingerprintHasher(u64);
+
+impl Default for FingerprintHasher {
+    fn default() -> Self {
+        FingerprintHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for FingerprintHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl<T: TileType + Default + Debug + Clone> Maze<T> {
+    /// Compare two mazes ignoring any marks left by robots
+    pub fn eq_ignoring_marks(&self, other: &Maze<T>) -> bool
+    where
+        T: PartialEq,
+    {
+        Unmarked(self) == Unmarked(other)
+    }
+
+    /// Copy of the maze with any robot marks removed
+    pub fn unmarked(&self) -> Maze<T> {
+        let mut maze = self.clone();
+        maze.grid.iter_mut().flatten().for_each(|tile| *tile = tile.unmarked());
+        maze
+    }
+
+    /// Copy of the maze with a symmetry applied, including the start and goal
+    pub fn transformed(&self, symmetry: Symmetry) -> Maze<T> {
+        let (width, height) = if symmetry.swaps_axes() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        let mut maze = Maze::new(width, height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let tile = self.grid[y][x].clone();
+                maze.set_cell(symmetry.apply(Point(x, y), self.width, self.height), tile);
+            }
+        }
+
+        if self.width > 0 && self.height > 0 {
+            maze.set_start(symmetry.apply(self.start, self.width, self.height));
+            maze.set_finish(symmetry.apply(self.goal, self.width, self.height));
+        }
+        maze
+    }
+
+    /// Canonical fingerprint of the maze. Any rotation or reflection of a maze
+    /// has the same fingerprint, and robot marks are ignored.
+    pub fn fingerprint(&self) -> u64 {
+        Symmetry::ALL
+            .iter()
+            .map(|symmetry| {
+                let maze = self.transformed(*symmetry);
+                let mut hasher = FingerprintHasher::default();
+                for value in [maze.width, maze.height, maze.start.0, maze.start.1, maze.goal.0, maze.goal.1] {
+                    hasher.write(&(value as u64).to_le_bytes());
+                }
+                for tile in maze.grid.iter().flatten() {
+                    hasher.write(&tile.unmarked().code());
+                }
+                hasher.finish()
+            })
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// List the differences going from this maze to `other`
+    pub fn diff(&self, other: &Maze<T>) -> MazeDiff<T>
+    where
+        T: PartialEq,
+    {
+        let mut cells = Vec::new();
+        for y in 0..self.height.max(other.height) {
+            for x in 0..self.width.max(other.width) {
+                let point = Point(x, y);
+                let before = self.get_cell(point);
+                let after = other.get_cell(point);
+                if before != after {
+                    cells.push(CellChange {
+                        point,
+                        before: before.cloned(),
+                        after: after.cloned(),
+                    });
+                }
+            }
+        }
+
+        let size = (self.width, self.height) != (other.width, other.height);
+        MazeDiff {
+            size: size.then_some(((self.width, self.height), (other.width, other.height))),
+            start: (self.start != other.start).then_some((self.start, other.start)),
+            goal: (self.goal != other.goal).then_some((self.goal, other.goal)),
+            cells,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::Tile;
+    use crate::generation::{actual_prim_generator::GappedPrimGenerator, Generator};
+
+    #[test]
+    fn fingerprint_is_invariant_under_symmetries() {
+        let maze = GappedPrimGenerator::new().generate_maze();
+        let fingerprint = maze.fingerprint();
+
+        for symmetry in Symmetry::ALL {
+            let transformed = maze.transformed(symmetry);
+            assert_eq!(fingerprint, transformed.fingerprint(), "{:?}", symmetry);
+        }
+
+        let mut marked = maze.clone();
+        marked.set_cell(maze.get_start(), Tile::BeenBefore);
+        assert_ne!(maze, marked);
+        assert!(maze.eq_ignoring_marks(&marked));
+        assert_eq!(fingerprint, marked.fingerprint());
+
+        // Fingerprints can be stored, so mustn't change between builds
+        let mut small: Maze<Tile> = Maze::new(3, 2);
+        small.set_cell(Point(1, 0), Tile::Wall);
+        small.set_finish(Point(2, 1));
+        assert_eq!(small.fingerprint(), 406552750728774181);
+    }
+
+    #[test]
+    fn diff_lists_changes() {
+        let mut before: Maze<Tile> = Maze::new(3, 3);
+        before.set_finish(Point(2, 2));
+        let mut after = before.clone();
+        after.set_cell(Point(1, 1), Tile::Wall);
+        after.set_finish(Point(2, 1));
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.start, None);
+        assert_eq!(diff.goal, Some((Point(2, 2), Point(2, 1))));
+        assert_eq!(
+            diff.cells,
+            vec![CellChange { point: Point(1, 1), before: Some(Tile::Passage), after: Some(Tile::Wall) }]
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{HashSet, VecDeque};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    execution::{rules::RunState, Heading, KeyColour, Tile},
+    generation::{Generator, Maze},
+    solving::{path_solver::ShortestPathSolver, Solver},
+    Point,
+};
+
+/// Options for the `KeyDoorGenerator`
+#[derive(Debug, Clone, Copy)]
+pub struct KeyDoorOptions<O> {
+    /// Options passed on to the wrapped generator
+    pub base: O,
+    /// Number of locked doors to place, at most one for each key colour
+    pub doors: usize,
+}
+
+impl<O: Default> Default for KeyDoorOptions<O> {
+    fn default() -> Self {
+        KeyDoorOptions {
+            base: O::default(),
+            doors: 2,
+        }
+    }
+}
+
+/// Wraps another generator and locks its route to the goal with coloured doors.
+/// Each door's key is placed somewhere reachable using only the keys before it,
+/// so the maze can always be solved.
+pub struct KeyDoorGenerator<G: Generator<Tiles = Tile>> {
+    generator: G,
+    options: KeyDoorOptions<G::Options>,
+}
+
+impl<G> Generator for KeyDoorGenerator<G>
+where
+    G: Generator<Tiles = Tile>,
+    G::Options: Clone,
+{
+    type Options = KeyDoorOptions<G::Options>;
+    type Tiles = Tile;
+
+    fn new() -> Self {
+        KeyDoorGenerator {
+            generator: G::new(),
+            options: Default::default(),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "Key and Door Generator"
+    }
+
+    fn get_description(&self) -> &str {
+        "Adds locked doors and their keys to another generator's mazes"
+    }
+
+    fn set_options(&mut self, options: Self::Options) {
+        self.generator.set_options(options.base.clone());
+        self.options = options;
+    }
+
+    fn get_options(&self) -> Self::Options {
+        self.options.clone()
+    }
+
+    fn generate_maze(&mut self) -> Maze<Self::Tiles> {
+        let mut maze = self.generator.generate_maze();
+        let mut rng = rand::thread_rng();
+
+        let path = match ShortestPathSolver::new().solve(&maze) {
+            Some(solution) => solution.get_path().to_vec(),
+            None => return maze,
+        };
+
+        // Doors go on the route, in the order the keys will be collected.
+        // The first step is left open so there's always somewhere for the first key.
+        let mut candidates: Vec<usize> = (2..path.len().saturating_sub(1))
+            .filter(|i| is_plain(maze.get_cell(path[*i])))
+            .collect();
+        candidates.shuffle(&mut rng);
+        let mut doors: Vec<usize> = candidates
+            .into_iter()
+            .take(self.options.doors.min(KeyColour::ALL.len()))
+            .collect();
+        doors.sort();
+
+        let colours = &KeyColour::ALL[..doors.len()];
+        for (index, colour) in doors.iter().zip(colours) {
+            maze.set_cell(path[*index], Tile::Door(*colour));
+        }
+
+        let on_path: HashSet<Point> = path.iter().copied().collect();
+        let mut state = RunState::default();
+        for colour in colours {
+            let reachable: Vec<Point> = reachable_from(&maze, maze.get_start(), &state)
+                .into_iter()
+                .filter(|point| *point != maze.get_start() && *point != maze.get_finish())
+                .filter(|point| is_plain(maze.get_cell(*point)))
+                .collect();
+
+            // Prefer hiding the key off the route so the robot has to go looking for it
+            let off_path: Vec<Point> = reachable.iter().copied().filter(|point| !on_path.contains(point)).collect();
+            let choices = if off_path.is_empty() { &reachable } else { &off_path };
+            if choices.is_empty() {
+                continue;
+            }
+
+            let point = choices[rng.gen_range(0..choices.len())];
+            maze.set_cell(point, Tile::Key(*colour));
+            state.add_key(*colour);
+        }
+
+        // Any door whose key couldn't be placed is removed again
+        for index in doors {
+            if let Some(Tile::Door(colour)) = maze.get_cell(path[index]) {
+                if !state.get_inventory().has_key(*colour) {
+                    maze.set_cell(path[index], Tile::Passage);
+                }
+            }
+        }
+
+        maze
+    }
+}
+
+/// Tiles which can be replaced by a key or a door
+fn is_plain(tile: Option<&Tile>) -> bool {
+    matches!(tile, Some(Tile::Passage | Tile::Mud | Tile::Sand | Tile::Road))
+}
+
+/// Every point reachable from `start` when carrying the keys in `state`
+fn reachable_from(maze: &Maze<Tile>, start: Point, state: &RunState) -> Vec<Point> {
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut points = Vec::new();
+
+    while let Some(point) = queue.pop_front() {
+        points.push(point);
+        for heading in Heading::ALL {
+            if let Some(next) = heading.next_point(point) {
+                let open = maze.get_cell(next).is_some_and(|tile| state.can_enter(*tile, heading));
+                if open && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::actual_prim_generator::GappedPrimGenerator;
+    use crate::GeneratorOptions;
+
+    #[test]
+    fn generated_mazes_are_solvable() {
+        for _ in 0..10 {
+            let mut generator = KeyDoorGenerator::<GappedPrimGenerator>::new();
+            generator.set_options(KeyDoorOptions {
+                base: GeneratorOptions { width: 8, height: 8 },
+                doors: 3,
+            });
+
+            let maze = generator.generate_maze();
+            let doors = maze.get_grid().iter().flatten().filter(|tile| matches!(tile, Tile::Door(_))).count();
+            assert!(doors > 0);
+            assert!(ShortestPathSolver::new().solve(&maze).is_some());
+        }
+    }
+}
@@ -1,6 +1,9 @@
 pub mod blank_generator;
 pub mod prim_generator;
 pub mod actual_prim_generator;
+pub mod compare;
+pub mod key_door_generator;
+pub mod terrain_generator;
 
 use std::fmt::Debug;
 
@@ -43,7 +46,7 @@ pub trait Generator {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Maze<T: TileType + Default> {
     width: usize,
     height: usize,
@@ -100,14 +103,14 @@ impl <T: TileType + Default + Debug + Clone> Maze<T> {
     /// 0 <= point.x < width
     /// 0 <= point.y < height
     pub fn get_cell(&self, point: Point) -> Option<&T> {
-        let col = self.grid.get(point.get_y() as usize)?;
-        col.get(point.get_x() as usize)
+        let col = self.grid.get(point.get_y())?;
+        col.get(point.get_x())
     }
 
     /// Set the cell at the point to a type
     pub fn set_cell(&mut self, point: Point, typ: T) {
-        if let Some(col) = self.grid.get_mut(point.get_y() as usize) {
-            col[point.get_x() as usize] = typ;
+        if let Some(col) = self.grid.get_mut(point.get_y()) {
+            col[point.get_x()] = typ;
         }
     }
 
@@ -93,7 +93,7 @@ impl Generator for PrimGenerator {
         // Add neighbours to frontier
         let h = self.options.height as usize - 2;
         let w = self.options.width as usize - 2;
-        vec![(w - 1, h), (w, h - 1)]
+        [(w - 1, h), (w, h - 1)]
             .iter()
             .for_each(|p| frontier.push(*p));
 
@@ -169,7 +169,7 @@ impl Generator for PrimGenerator {
 
 impl PrimGenerator {
     fn grid_get_point(
-        grid: &Vec<Vec<CellType>>,
+        grid: &[Vec<CellType>],
         point: (usize, usize),
     ) -> (Option<&CellType>, (usize, usize)) {
         let (x, y) = point;
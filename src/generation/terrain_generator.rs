@@ -0,0 +1,141 @@
+use rand::Rng;
+
+use crate::{
+    execution::{Heading, Tile},
+    generation::{Generator, Maze},
+    Point,
+};
+
+/// Options for the `TerrainGenerator`. Each of the terrain values is the
+/// chance that a passage becomes that terrain.
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainOptions<O> {
+    /// Options passed on to the wrapped generator
+    pub base: O,
+    pub mud: f64,
+    pub sand: f64,
+    pub road: f64,
+    /// Chance that a wall between two passages is knocked through,
+    /// giving alternate routes so the fastest route isn't always the shortest.
+    /// Kept between 0 and 1 when the options are set.
+    pub loops: f64,
+}
+
+impl<O: Default> Default for TerrainOptions<O> {
+    fn default() -> Self {
+        TerrainOptions {
+            base: O::default(),
+            mud: 0.1,
+            sand: 0.15,
+            road: 0.2,
+            loops: 0.1,
+        }
+    }
+}
+
+/// Wraps another generator and covers its passages in terrain which
+/// takes a different amount of time to cross.
+pub struct TerrainGenerator<G: Generator<Tiles = Tile>> {
+    generator: G,
+    options: TerrainOptions<G::Options>,
+}
+
+impl<G> Generator for TerrainGenerator<G>
+where
+    G: Generator<Tiles = Tile>,
+    G::Options: Clone,
+{
+    type Options = TerrainOptions<G::Options>;
+    type Tiles = Tile;
+
+    fn new() -> Self {
+        TerrainGenerator {
+            generator: G::new(),
+            options: Default::default(),
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        "Terrain Generator"
+    }
+
+    fn get_description(&self) -> &str {
+        "Adds mud, sand and road to another generator's mazes"
+    }
+
+    fn set_options(&mut self, options: Self::Options) {
+        self.generator.set_options(options.base.clone());
+        let loops = if options.loops.is_nan() { 0.0 } else { options.loops.clamp(0.0, 1.0) };
+        self.options = TerrainOptions { loops, ..options };
+    }
+
+    fn get_options(&self) -> Self::Options {
+        self.options.clone()
+    }
+
+    fn generate_maze(&mut self) -> Maze<Self::Tiles> {
+        let mut maze = self.generator.generate_maze();
+        let mut rng = rand::thread_rng();
+
+        // Knock through walls which separate two passages
+        for y in 1..maze.get_height().saturating_sub(1) {
+            for x in 1..maze.get_width().saturating_sub(1) {
+                let point = Point(x, y);
+                if maze.get_cell(point) != Some(&Tile::Wall) {
+                    continue;
+                }
+
+                let open = |a: Heading, b: Heading| {
+                    [a, b].iter().all(|h| h.next_point(point).is_some_and(|p| maze.can_move(p)))
+                };
+                let between = open(Heading::North, Heading::South) || open(Heading::East, Heading::West);
+
+                if between && rng.gen_bool(self.options.loops) {
+                    maze.set_cell(point, Tile::Passage);
+                }
+            }
+        }
+
+        for y in 0..maze.get_height() {
+            for x in 0..maze.get_width() {
+                let point = Point(x, y);
+                if maze.get_cell(point) != Some(&Tile::Passage)
+                    || point == maze.get_start()
+                    || point == maze.get_finish()
+                {
+                    continue;
+                }
+
+                let roll: f64 = rng.gen();
+                let terrain = if roll < self.options.mud {
+                    Tile::Mud
+                } else if roll < self.options.mud + self.options.sand {
+                    Tile::Sand
+                } else if roll < self.options.mud + self.options.sand + self.options.road {
+                    Tile::Road
+                } else {
+                    Tile::Passage
+                };
+                maze.set_cell(point, terrain);
+            }
+        }
+
+        maze
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::blank_generator::BlankGenerator;
+
+    #[test]
+    fn loop_chance_is_kept_in_range() {
+        let mut generator = TerrainGenerator::<BlankGenerator>::new();
+        for (loops, expected) in [(5.0, 1.0), (-1.0, 0.0), (f64::NAN, 0.0), (0.25, 0.25)] {
+            generator.set_options(TerrainOptions { loops, ..Default::default() });
+            assert_eq!(generator.get_options().loops, expected);
+            generator.generate_maze();
+        }
+    }
+}
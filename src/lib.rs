@@ -7,8 +7,10 @@ pub mod generation;
 
 pub mod execution;
 
+pub mod solving;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct Point(pub usize, pub usize);
 
 impl Point {
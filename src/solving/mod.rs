@@ -0,0 +1,102 @@
+pub mod path_solver;
+
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+use std::hash::Hash;
+
+use crate::Point;
+use crate::execution::TileType;
+use crate::generation::Maze;
+
+/// Something which finds a route through a maze
+pub trait Solver {
+
+    type Tiles: TileType + Default;
+
+    /// Creates a new solver of this type
+    fn new() -> Self;
+
+    /// Provides the name of the solver
+    fn get_name(&self) -> &str;
+
+    /// Provides the description of the solver
+    fn get_description(&self) -> &str {
+        "A maze solver"
+    }
+
+    /// Find a route from the start of the maze to the finish, if there is one
+    fn solve(&mut self, maze: &Maze<Self::Tiles>) -> Option<Solution>;
+}
+
+/// A route through a maze, from the start point to the finish point inclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    path: Vec<Point>,
+    cost: u64,
+}
+
+impl Solution {
+    pub fn new(path: Vec<Point>, cost: u64) -> Self {
+        Solution { path, cost }
+    }
+
+    /// Every cell the route passes through
+    pub fn get_path(&self) -> &[Point] {
+        &self.path
+    }
+
+    /// Number of moves needed to follow the route
+    pub fn get_steps(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+
+    /// Number of ticks it takes to follow the route
+    pub fn get_cost(&self) -> u64 {
+        self.cost
+    }
+}
+
+/// Lowest weight search from `start` to any state where `is_goal` holds.
+/// `successors` lists the states reachable in one move along with the weight of that move.
+/// Returns every state along the way and the total weight.
+pub(crate) fn lowest_weight_path<S, G, F>(start: S, is_goal: G, mut successors: F) -> Option<(Vec<S>, u64)>
+where
+    S: Clone + Eq + Hash + Ord,
+    G: Fn(&S) -> bool,
+    F: FnMut(&S) -> Vec<(S, u64)>,
+{
+    let mut best: HashMap<S, u64> = HashMap::new();
+    let mut previous: HashMap<S, S> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    best.insert(start.clone(), 0);
+    queue.push(Reverse((0, start)));
+
+    while let Some(Reverse((weight, state))) = queue.pop() {
+        if best.get(&state).is_some_and(|w| *w < weight) {
+            continue;
+        }
+
+        if is_goal(&state) {
+            let mut path = vec![state.clone()];
+            let mut current = state;
+            while let Some(prev) = previous.get(&current) {
+                path.push(prev.clone());
+                current = prev.clone();
+            }
+            path.reverse();
+            return Some((path, weight));
+        }
+
+        for (next, step) in successors(&state) {
+            let next_weight = weight + step;
+            if best.get(&next).is_none_or(|w| next_weight < *w) {
+                best.insert(next.clone(), next_weight);
+                previous.insert(next.clone(), state.clone());
+                queue.push(Reverse((next_weight, next)));
+            }
+        }
+    }
+
+    None
+}
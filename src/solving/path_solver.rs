@@ -0,0 +1,224 @@
+use crate::execution::{rules::{self, Move, RunState}, Heading, Tile, ROBOT_HEALTH};
+use crate::generation::Maze;
+
+use super::{lowest_weight_path, Solution, Solver};
+
+/// Finds the route with the fewest moves, ignoring how slow the tiles are to cross
+pub struct ShortestPathSolver;
+
+/// Finds the route which takes the fewest ticks, using the `cost()` of each tile
+pub struct FastestPathSolver;
+
+impl Solver for ShortestPathSolver {
+    type Tiles = Tile;
+
+    fn new() -> Self {
+        ShortestPathSolver
+    }
+
+    fn get_name(&self) -> &str {
+        "Shortest Path Solver"
+    }
+
+    fn get_description(&self) -> &str {
+        "Finds the route with the fewest steps"
+    }
+
+    fn solve(&mut self, maze: &Maze<Self::Tiles>) -> Option<Solution> {
+        solve_by(maze, Priority::Steps)
+    }
+}
+
+impl Solver for FastestPathSolver {
+    type Tiles = Tile;
+
+    fn new() -> Self {
+        FastestPathSolver
+    }
+
+    fn get_name(&self) -> &str {
+        "Fastest Path Solver"
+    }
+
+    fn get_description(&self) -> &str {
+        "Finds the route which takes the least time to follow"
+    }
+
+    fn solve(&mut self, maze: &Maze<Self::Tiles>) -> Option<Solution> {
+        solve_by(maze, Priority::Time)
+    }
+}
+
+/// What a solver is trying to minimise. Ties are broken by the other one.
+#[derive(Clone, Copy)]
+enum Priority {
+    Steps,
+    Time,
+}
+
+/// Both the steps and the time are packed into the search weight,
+/// with the one being minimised in the upper half
+const PRIORITY_SHIFT: u32 = 32;
+
+/// Search the maze, keeping track of any keys and switches along the way
+/// and the robot's health so it avoids anything which would destroy it
+fn solve_by(maze: &Maze<Tile>, priority: Priority) -> Option<Solution> {
+    let goal = maze.get_finish();
+    let (path, weight) = lowest_weight_path(
+        (maze.get_start(), RunState::default(), ROBOT_HEALTH),
+        |(point, _, _)| *point == goal,
+        |(point, state, health)| {
+            Heading::ALL
+                .iter()
+                .filter_map(|heading| {
+                    let mut next = *state;
+                    match rules::resolve_move(maze, &mut next, *point, *heading) {
+                        Move::Moved { to, cost, damage } if damage < *health => {
+                            let weight = match priority {
+                                Priority::Steps => (1 << PRIORITY_SHIFT) + cost as u64,
+                                Priority::Time => ((cost as u64) << PRIORITY_SHIFT) + 1,
+                            };
+                            Some(((to, next, health - damage), weight))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        },
+    )?;
+
+    let cost = match priority {
+        Priority::Steps => weight & ((1 << PRIORITY_SHIFT) - 1),
+        Priority::Time => weight >> PRIORITY_SHIFT,
+    };
+    Some(Solution::new(path.into_iter().map(|(point, _, _)| point).collect(), cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::{KeyColour, SwitchGroup};
+    use crate::Point;
+
+    #[test]
+    fn fastest_route_avoids_mud() {
+        // S M G
+        // R R R
+        let mut maze: Maze<Tile> = Maze::new(3, 2);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(2, 0));
+        maze.set_cell(Point(1, 0), Tile::Mud);
+        for x in 0..3 {
+            maze.set_cell(Point(x, 1), Tile::Road);
+        }
+
+        let shortest = ShortestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(shortest.get_steps(), 2);
+        assert_eq!(shortest.get_cost(), 7);
+
+        let fastest = FastestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(fastest.get_steps(), 4);
+        assert_eq!(fastest.get_cost(), 5);
+        assert_eq!(fastest.get_path().first(), Some(&Point(0, 0)));
+        assert_eq!(fastest.get_path().last(), Some(&Point(2, 0)));
+    }
+
+    #[test]
+    fn collects_key_before_door() {
+        // S . R G
+        // r # # #
+        let mut maze: Maze<Tile> = Maze::new(4, 2);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(3, 0));
+        maze.set_cell(Point(2, 0), Tile::Door(KeyColour::Red));
+        maze.set_cell(Point(0, 1), Tile::Key(KeyColour::Red));
+        for x in 1..4 {
+            maze.set_cell(Point(x, 1), Tile::Wall);
+        }
+
+        let solution = ShortestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(
+            solution.get_path(),
+            &[Point(0, 0), Point(0, 1), Point(0, 0), Point(1, 0), Point(2, 0), Point(3, 0)]
+        );
+
+        maze.set_cell(Point(0, 1), Tile::Passage);
+        assert_eq!(ShortestPathSolver::new().solve(&maze), None);
+    }
+
+    #[test]
+    fn follows_teleporters_and_one_way_tiles() {
+        // S 0 # 0 #
+        // . ← . . G
+        let mut maze: Maze<Tile> = Maze::new(5, 2);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(4, 1));
+        maze.set_cell(Point(1, 0), Tile::Teleporter(0));
+        maze.set_cell(Point(2, 0), Tile::Wall);
+        maze.set_cell(Point(3, 0), Tile::Teleporter(0));
+        maze.set_cell(Point(4, 0), Tile::Wall);
+        maze.set_cell(Point(1, 1), Tile::OneWay(Heading::West));
+
+        let solution = ShortestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(solution.get_path(), &[Point(0, 0), Point(3, 0), Point(3, 1), Point(4, 1)]);
+
+        // Without the teleporters the one way tile blocks the only other route
+        maze.set_cell(Point(1, 0), Tile::Passage);
+        maze.set_cell(Point(3, 0), Tile::Passage);
+        maze.set_cell(Point(2, 0), Tile::Wall);
+        assert_eq!(ShortestPathSolver::new().solve(&maze), None);
+    }
+
+    #[test]
+    fn switches_open_walls_in_their_group() {
+        // S % ▓ G
+        let group = SwitchGroup::new(SwitchGroup::MAX).unwrap();
+        let mut maze: Maze<Tile> = Maze::new(4, 1);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(3, 0));
+        maze.set_cell(Point(1, 0), Tile::Switch(group));
+        maze.set_cell(Point(2, 0), Tile::SwitchWall { group, open: false });
+        assert_eq!(ShortestPathSolver::new().solve(&maze).unwrap().get_steps(), 3);
+
+        // A switch in another group leaves the wall closed
+        maze.set_cell(Point(1, 0), Tile::Switch(SwitchGroup::new(0).unwrap()));
+        assert_eq!(ShortestPathSolver::new().solve(&maze), None);
+        assert_eq!(SwitchGroup::new(SwitchGroup::MAX + 1), None);
+    }
+
+    #[test]
+    fn slides_across_ice() {
+        // S * * .
+        // # # * G
+        let mut maze: Maze<Tile> = Maze::new(4, 2);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(3, 1));
+        maze.set_cell(Point(1, 0), Tile::Ice);
+        maze.set_cell(Point(2, 0), Tile::Ice);
+        maze.set_cell(Point(0, 1), Tile::Wall);
+        maze.set_cell(Point(1, 1), Tile::Wall);
+        maze.set_cell(Point(2, 1), Tile::Ice);
+
+        // Can't stop on (2, 0) to turn down, so has to go round
+        let solution = ShortestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(solution.get_path(), &[Point(0, 0), Point(3, 0), Point(3, 1)]);
+        assert_eq!(solution.get_cost(), 6);
+    }
+
+    #[test]
+    fn avoids_pits_and_too_much_damage() {
+        // S O G
+        // ! . .
+        let mut maze: Maze<Tile> = Maze::new(3, 2);
+        maze.set_start(Point(0, 0));
+        maze.set_finish(Point(2, 0));
+        maze.set_cell(Point(1, 0), Tile::Pit);
+        maze.set_cell(Point(0, 1), Tile::Damage(60));
+
+        let solution = ShortestPathSolver::new().solve(&maze).unwrap();
+        assert_eq!(solution.get_steps(), 4);
+
+        maze.set_cell(Point(0, 1), Tile::Damage(100));
+        assert_eq!(ShortestPathSolver::new().solve(&maze), None);
+    }
+}